@@ -0,0 +1,99 @@
+//! Command-line argument parsing.
+
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: vectorcade-render <INPUT> [OPTIONS]
+
+Renders a display-list recording (.vcdl) or a single-frame JSON/RON display
+list (.json/.ron) to PNG.
+
+Options:
+  -o, --output <PATH>     Output PNG (default: frame.png). Recordings write
+                          <stem>_<frame>.png unless --frame is given
+      --size <WxH>        Image size (default: recording viewport or 640x480)
+      --backend <NAME>    gpu (headless wgpu, default) or software
+      --msaa <N>          Multisample count 1, 2, 4, 8 or 16, gpu only; falls
+                          back to the closest supported count (default: 4)
+      --glow <F>          Phosphor glow intensity, gpu only (default: 1.0)
+      --frame <N>         Render only frame N of a recording
+  -h, --help              Print this help
+";
+
+/// Which renderer draws the frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Gpu,
+    Software,
+}
+
+/// Parsed command-line options.
+#[derive(Debug)]
+pub struct Args {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub size: Option<(u32, u32)>,
+    pub backend: Backend,
+    pub msaa: u32,
+    pub glow: f32,
+    pub frame: Option<u64>,
+}
+
+impl Args {
+    /// Parse arguments (without the program name); `None` means help was requested.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut it = args.into_iter();
+        let mut input = None;
+        let mut parsed = Args {
+            input: PathBuf::new(),
+            output: PathBuf::from("frame.png"),
+            size: None,
+            backend: Backend::Gpu,
+            msaa: 4,
+            glow: 1.0,
+            frame: None,
+        };
+
+        while let Some(arg) = it.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-o" | "--output" => parsed.output = value(&mut it, &arg)?.into(),
+                "--size" => parsed.size = Some(parse_size(&value(&mut it, &arg)?)?),
+                "--backend" => parsed.backend = parse_backend(&value(&mut it, &arg)?)?,
+                "--msaa" => parsed.msaa = parse_number(&value(&mut it, &arg)?, &arg)?,
+                "--glow" => parsed.glow = parse_number(&value(&mut it, &arg)?, &arg)?,
+                "--frame" => parsed.frame = Some(parse_number(&value(&mut it, &arg)?, &arg)?),
+                _ if arg.starts_with('-') => return Err(format!("unknown option '{arg}'")),
+                _ if input.is_some() => return Err(format!("unexpected argument '{arg}'")),
+                _ => input = Some(PathBuf::from(arg)),
+            }
+        }
+        parsed.input = input.ok_or("missing INPUT")?;
+        Ok(Some(parsed))
+    }
+}
+
+fn value(it: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    it.next().ok_or_else(|| format!("{option} requires a value"))
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str, option: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid value '{s}' for {option}"))
+}
+
+fn parse_size(s: &str) -> Result<(u32, u32), String> {
+    let (w, h) = s.split_once(['x', 'X']).ok_or_else(|| format!("invalid size '{s}', expected WxH"))?;
+    let (w, h): (u32, u32) = (parse_number(w, "--size")?, parse_number(h, "--size")?);
+    if w == 0 || h == 0 {
+        return Err(format!("invalid size '{s}', dimensions must be non-zero"));
+    }
+    Ok((w, h))
+}
+
+fn parse_backend(s: &str) -> Result<Backend, String> {
+    match s {
+        "gpu" => Ok(Backend::Gpu),
+        "software" => Ok(Backend::Software),
+        other => Err(format!("unknown backend '{other}', expected gpu or software")),
+    }
+}
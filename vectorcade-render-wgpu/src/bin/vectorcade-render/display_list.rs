@@ -0,0 +1,125 @@
+//! Input loading: recordings and single-frame JSON/RON display lists.
+//!
+//! Text display lists use serde mirrors of `DrawCmd`, e.g. in JSON:
+//!
+//! ```text
+//! [
+//!   { "Clear": { "color": [0, 0, 0, 1] } },
+//!   { "Line": { "a": [-0.5, 0], "b": [0.5, 0], "stroke": { "color": [1, 1, 1, 1], "width_px": 2 } } },
+//!   { "PushTransform": [1, 0, 0, 0, 1, 0, 0.25, 0, 1] },
+//!   "PopTransform"
+//! ]
+//! ```
+//!
+//! Transforms are column-major `Mat3`s; `closed`, `glow` and `style` are optional.
+
+use glam::{Mat3, Vec2};
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use vectorcade_render_wgpu::Replayer;
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+use vectorcade_shared::font::FontStyleId;
+
+/// A loaded input file.
+pub enum Input {
+    Frame(Vec<DrawCmd>),
+    Recording(Replayer<BufReader<File>>),
+}
+
+impl Input {
+    /// Viewport size stored in the input, if any.
+    pub fn viewport(&self) -> Option<(u32, u32)> {
+        match self {
+            Input::Frame(_) => None,
+            Input::Recording(replayer) => Some(replayer.viewport()),
+        }
+    }
+}
+
+/// Open `path`, choosing the format by extension.
+pub fn open(path: &Path) -> Result<Input, String> {
+    let ext = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    let read = || std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()));
+    let cmds: Vec<Cmd> = match ext.as_deref() {
+        Some("json") => serde_json::from_str(&read()?).map_err(|e| format!("{}: {e}", path.display()))?,
+        Some("ron") => ron::from_str(&read()?).map_err(|e| format!("{}: {e}", path.display()))?,
+        _ => {
+            let file = File::open(path).map_err(|e| format!("cannot open {}: {e}", path.display()))?;
+            let replayer = Replayer::new(BufReader::new(file)).map_err(|e| format!("{}: {e}", path.display()))?;
+            return Ok(Input::Recording(replayer));
+        }
+    };
+    Ok(Input::Frame(cmds.into_iter().map(DrawCmd::from).collect()))
+}
+
+#[derive(Deserialize)]
+struct StrokeDef {
+    color: [f32; 4],
+    width_px: f32,
+    #[serde(default)]
+    glow: f32,
+}
+
+#[derive(Deserialize)]
+enum Cmd {
+    Clear {
+        color: [f32; 4],
+    },
+    Line {
+        a: [f32; 2],
+        b: [f32; 2],
+        stroke: StrokeDef,
+    },
+    Polyline {
+        pts: Vec<[f32; 2]>,
+        #[serde(default)]
+        closed: bool,
+        stroke: StrokeDef,
+    },
+    Text {
+        pos: [f32; 2],
+        text: String,
+        size_px: f32,
+        color: [f32; 4],
+        #[serde(default)]
+        style: u32,
+    },
+    PushTransform([f32; 9]),
+    PopTransform,
+}
+
+fn rgba([r, g, b, a]: [f32; 4]) -> Rgba {
+    Rgba(r, g, b, a)
+}
+
+impl From<StrokeDef> for Stroke {
+    fn from(s: StrokeDef) -> Self {
+        Stroke { color: rgba(s.color), width_px: s.width_px, glow: s.glow }
+    }
+}
+
+impl From<Cmd> for DrawCmd {
+    fn from(cmd: Cmd) -> Self {
+        match cmd {
+            Cmd::Clear { color } => DrawCmd::Clear { color: rgba(color) },
+            Cmd::Line { a, b, stroke } => {
+                DrawCmd::Line(Line2 { a: Vec2::from(a), b: Vec2::from(b), stroke: stroke.into() })
+            }
+            Cmd::Polyline { pts, closed, stroke } => {
+                DrawCmd::Polyline { pts: pts.into_iter().map(Vec2::from).collect(), closed, stroke: stroke.into() }
+            }
+            Cmd::Text { pos, text, size_px, color, style } => DrawCmd::Text {
+                pos: Vec2::from(pos),
+                text,
+                size_px,
+                color: rgba(color),
+                style: FontStyleId(style),
+            },
+            Cmd::PushTransform(cols) => DrawCmd::PushTransform(Mat3::from_cols_array(&cols)),
+            Cmd::PopTransform => DrawCmd::PopTransform,
+        }
+    }
+}
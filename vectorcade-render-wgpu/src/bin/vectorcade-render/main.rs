@@ -0,0 +1,80 @@
+//! `vectorcade-render`: render display-list recordings and JSON/RON display
+//! lists to PNG images without running the game.
+
+mod args;
+mod display_list;
+mod renderer;
+
+use args::{Args, USAGE};
+use display_list::Input;
+use renderer::Renderer;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const DEFAULT_SIZE: (u32, u32) = (640, 480);
+
+fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(frames) => {
+            eprintln!("wrote {frames} frame(s)");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<u64, String> {
+    let input = display_list::open(&args.input)?;
+    let size = args.size.or(input.viewport()).unwrap_or(DEFAULT_SIZE);
+    let mut renderer = Renderer::new(args, size);
+
+    match input {
+        Input::Frame(cmds) => {
+            save(&renderer.draw(&cmds)?, &args.output)?;
+            Ok(1)
+        }
+        Input::Recording(mut replayer) => {
+            let mut written = 0;
+            for index in 0.. {
+                let Some(frame) = replayer.next_frame().map_err(|e| format!("frame {index}: {e}"))? else { break };
+                match args.frame {
+                    Some(wanted) if wanted != index => continue,
+                    Some(_) => {
+                        save(&renderer.draw(&frame.cmds)?, &args.output)?;
+                        return Ok(1);
+                    }
+                    None => save(&renderer.draw(&frame.cmds)?, &numbered(&args.output, index))?,
+                }
+                written += 1;
+            }
+            match args.frame {
+                Some(wanted) => Err(format!("recording has no frame {wanted}")),
+                None => Ok(written),
+            }
+        }
+    }
+}
+
+/// `out.png` -> `out_00042.png`.
+fn numbered(path: &Path, index: u64) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("frame");
+    path.with_file_name(format!("{stem}_{index:05}.png"))
+}
+
+fn save(image: &vectorcade_render_wgpu::RgbaImage, path: &Path) -> Result<(), String> {
+    image.save_png(path).map_err(|e| format!("cannot write {}: {e}", path.display()))
+}
@@ -0,0 +1,56 @@
+//! The renderer frames are drawn with: headless wgpu or the software rasterizer.
+
+use crate::args::{Args, Backend};
+use vectorcade_render_wgpu::{RgbaImage, SoftwareRenderer, VectorRenderer, WgpuRenderer};
+use vectorcade_shared::draw::DrawCmd;
+
+/// Multisample count of the software rasterizer.
+const SOFTWARE_MSAA: u32 = 4;
+
+pub enum Renderer {
+    Gpu(Box<WgpuRenderer>),
+    Software(Box<SoftwareRenderer>),
+}
+
+impl Renderer {
+    /// Create the backend selected by `args`, falling back to software when no GPU is available.
+    pub fn new(args: &Args, (width, height): (u32, u32)) -> Self {
+        if args.backend == Backend::Software {
+            return software(args, width, height);
+        }
+        match pollster::block_on(WgpuRenderer::new_headless_with_sample_count(width, height, args.msaa)) {
+            Ok(mut renderer) => {
+                if renderer.sample_count() != args.msaa {
+                    eprintln!("warning: {}x MSAA is not supported, using {}x", args.msaa, renderer.sample_count());
+                }
+                renderer.set_glow_intensity(args.glow);
+                Self::Gpu(Box::new(renderer))
+            }
+            Err(e) => {
+                eprintln!("warning: {e}; using the software renderer");
+                software(args, width, height)
+            }
+        }
+    }
+
+    /// Render `cmds` and read back the resulting image.
+    pub fn draw(&mut self, cmds: &[DrawCmd]) -> Result<RgbaImage, String> {
+        match self {
+            Self::Gpu(renderer) => {
+                renderer.render(cmds);
+                renderer.capture_frame().map_err(|e| e.to_string())
+            }
+            Self::Software(renderer) => {
+                renderer.render(cmds);
+                Ok(renderer.image().clone())
+            }
+        }
+    }
+}
+
+fn software(args: &Args, width: u32, height: u32) -> Renderer {
+    if args.msaa != SOFTWARE_MSAA {
+        eprintln!("warning: the software renderer always uses {SOFTWARE_MSAA}x MSAA");
+    }
+    Renderer::Software(Box::new(SoftwareRenderer::new(width, height)))
+}
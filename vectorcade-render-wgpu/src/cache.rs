@@ -0,0 +1,233 @@
+//! Tessellation cache for polylines repeated across frames.
+//!
+//! Games redraw the same ship and asteroid outlines every frame under
+//! different transforms. The cache keeps each polyline's mesh in local space,
+//! keyed on its untransformed points, closed flag and stroke. Transforms are
+//! applied in the vertex shader, so a hit only copies the vertices and points
+//! them at the current transform.
+
+use crate::tessellate::{Geometry, StrokeOutcome, StrokeScratch, Vertex, is_drawable};
+use glam::{Mat3, Vec2};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use vectorcade_shared::draw::Stroke;
+
+/// Frames an unused entry is kept by default.
+pub const DEFAULT_MAX_AGE: u32 = 60;
+
+/// Everything besides the points that affects the local-space mesh.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Params {
+    closed: bool,
+    /// Stroke color, width and glow, and `px_to_ndc`, as bits.
+    stroke: [u32; 7],
+}
+
+struct Entry {
+    pts: Vec<Vec2>,
+    params: Params,
+    geometry: Geometry,
+    last_used: u64,
+}
+
+impl Entry {
+    fn matches(&self, pts: &[Vec2], params: &Params) -> bool {
+        self.params == *params && self.pts == pts
+    }
+}
+
+/// Cache updates recorded by [`TessellationCache::tessellate_polyline_shared`].
+#[cfg(feature = "parallel")]
+#[derive(Default)]
+pub struct CacheLog {
+    touched: Vec<u64>,
+    added: Vec<(u64, Entry)>,
+}
+
+/// How the cache produced a polyline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lookup {
+    /// Copied from a cached mesh.
+    Hit,
+    /// Tessellated, and cached unless the cache is disabled.
+    Miss,
+    /// Not drawable; see [`StrokeOutcome::Skipped`].
+    Skipped,
+    /// lyon failed to tessellate it. Failures are not cached.
+    Failed,
+}
+
+/// Local-space meshes of recently drawn polylines.
+pub struct TessellationCache {
+    entries: HashMap<u64, Entry>,
+    frame: u64,
+    max_age: u32,
+    scratch: StrokeScratch,
+}
+
+impl TessellationCache {
+    /// Create a cache evicting entries unused for `max_age` frames; 0 disables it.
+    pub fn new(max_age: u32) -> Self {
+        Self { entries: HashMap::new(), frame: 0, max_age, scratch: StrokeScratch::new() }
+    }
+
+    /// Frames an unused entry is kept.
+    #[cfg(feature = "wgpu-backend")]
+    pub fn max_age(&self) -> u32 {
+        self.max_age
+    }
+
+    /// Change how long unused entries are kept; 0 disables and empties the cache.
+    pub fn set_max_age(&mut self, frames: u32) {
+        self.max_age = frames;
+        if frames == 0 {
+            self.entries.clear();
+        }
+    }
+
+    /// Append a polyline under `transform` to `geom`, returning whether the
+    /// mesh came from the cache or why nothing was appended.
+    pub fn tessellate_polyline(
+        &mut self,
+        pts: &[Vec2],
+        closed: bool,
+        stroke: &Stroke,
+        transform: Option<&Mat3>,
+        px_to_ndc: f32,
+        geom: &mut Geometry,
+    ) -> Lookup {
+        if !is_drawable(pts) {
+            return Lookup::Skipped;
+        }
+        if self.max_age == 0 {
+            return tessellate_uncached(&mut self.scratch, pts, closed, stroke, transform, px_to_ndc, geom);
+        }
+        let (key, params) = cache_key(pts, closed, stroke, px_to_ndc);
+        if let Some(entry) = self.entries.get_mut(&key).filter(|e| e.matches(pts, &params)) {
+            entry.last_used = self.frame;
+            append(&entry.geometry, transform, geom);
+            return Lookup::Hit;
+        }
+        let Some(entry) = new_entry(&mut self.scratch, pts, params, stroke, px_to_ndc, self.frame) else {
+            return Lookup::Failed;
+        };
+        append(&entry.geometry, transform, geom);
+        self.entries.insert(key, entry);
+        Lookup::Miss
+    }
+
+    /// Like [`tessellate_polyline`](Self::tessellate_polyline), but only reads
+    /// the cache so chunks can share it across threads. Misses are
+    /// tessellated with the chunk's `scratch`; hits and new meshes are
+    /// recorded in `log` and applied by [`commit`](Self::commit).
+    #[cfg(feature = "parallel")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn tessellate_polyline_shared(
+        &self,
+        pts: &[Vec2],
+        closed: bool,
+        stroke: &Stroke,
+        transform: Option<&Mat3>,
+        px_to_ndc: f32,
+        scratch: &mut StrokeScratch,
+        geom: &mut Geometry,
+        log: &mut CacheLog,
+    ) -> Lookup {
+        if !is_drawable(pts) {
+            return Lookup::Skipped;
+        }
+        if self.max_age == 0 {
+            return tessellate_uncached(scratch, pts, closed, stroke, transform, px_to_ndc, geom);
+        }
+        let (key, params) = cache_key(pts, closed, stroke, px_to_ndc);
+        if let Some(entry) = self.entries.get(&key).filter(|e| e.matches(pts, &params)) {
+            log.touched.push(key);
+            append(&entry.geometry, transform, geom);
+            return Lookup::Hit;
+        }
+        let Some(entry) = new_entry(scratch, pts, params, stroke, px_to_ndc, self.frame) else {
+            return Lookup::Failed;
+        };
+        append(&entry.geometry, transform, geom);
+        log.added.push((key, entry));
+        Lookup::Miss
+    }
+
+    /// Apply the hits and new meshes recorded in `log`.
+    #[cfg(feature = "parallel")]
+    pub fn commit(&mut self, log: CacheLog) {
+        for key in log.touched {
+            if let Some(entry) = self.entries.get_mut(&key) {
+                entry.last_used = self.frame;
+            }
+        }
+        self.entries.extend(log.added);
+    }
+
+    /// Finish a frame, evicting entries that have gone `max_age` frames unused.
+    pub fn end_frame(&mut self) {
+        self.frame += 1;
+        let (frame, max_age) = (self.frame, u64::from(self.max_age));
+        self.entries.retain(|_, e| frame - e.last_used <= max_age);
+    }
+}
+
+impl Default for TessellationCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_AGE)
+    }
+}
+
+fn new_entry(
+    scratch: &mut StrokeScratch,
+    pts: &[Vec2],
+    params: Params,
+    stroke: &Stroke,
+    px_to_ndc: f32,
+    frame: u64,
+) -> Option<Entry> {
+    let mut geometry = Geometry::new();
+    match scratch.stroke(pts, params.closed, stroke, px_to_ndc, 0, &mut geometry) {
+        StrokeOutcome::Tessellated => Some(Entry { pts: pts.to_vec(), params, geometry, last_used: frame }),
+        StrokeOutcome::Skipped | StrokeOutcome::Failed => None,
+    }
+}
+
+fn tessellate_uncached(
+    scratch: &mut StrokeScratch,
+    pts: &[Vec2],
+    closed: bool,
+    stroke: &Stroke,
+    transform: Option<&Mat3>,
+    px_to_ndc: f32,
+    geom: &mut Geometry,
+) -> Lookup {
+    let index = geom.push_transform(transform);
+    match scratch.stroke(pts, closed, stroke, px_to_ndc, index, geom) {
+        StrokeOutcome::Tessellated => Lookup::Miss,
+        StrokeOutcome::Skipped => Lookup::Skipped,
+        StrokeOutcome::Failed => Lookup::Failed,
+    }
+}
+
+/// Hash and comparison data of a polyline.
+fn cache_key(pts: &[Vec2], closed: bool, stroke: &Stroke, px_to_ndc: f32) -> (u64, Params) {
+    let c = stroke.color;
+    let stroke_bits = [c.0, c.1, c.2, c.3, stroke.width_px, stroke.glow, px_to_ndc].map(f32::to_bits);
+    let params = Params { closed, stroke: stroke_bits };
+    let mut hasher = DefaultHasher::new();
+    params.hash(&mut hasher);
+    pts.len().hash(&mut hasher);
+    for p in pts {
+        (p.x.to_bits(), p.y.to_bits()).hash(&mut hasher);
+    }
+    (hasher.finish(), params)
+}
+
+/// Append a local-space mesh drawn with `transform`.
+fn append(local: &Geometry, transform: Option<&Mat3>, geom: &mut Geometry) {
+    let base = geom.vertices.len() as u32;
+    let index = geom.push_transform(transform);
+    geom.vertices.extend(local.vertices.iter().map(|v| Vertex { transform: index, ..*v }));
+    geom.indices.extend(local.indices.iter().map(|i| i + base));
+}
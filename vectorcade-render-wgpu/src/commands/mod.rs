@@ -0,0 +1,236 @@
+//! Display-list walking shared by the renderers.
+//!
+//! Resolves `Clear`, the projection and the transform stack and tessellates
+//! strokes and text into one [`Geometry`] in NDC space.
+
+#[cfg(feature = "parallel")]
+mod parallel;
+
+use crate::RenderStats;
+use crate::cache::{Lookup, TessellationCache};
+use crate::state::RenderState;
+use crate::tessellate::{Geometry, LineInstance, StrokeOutcome, StrokeScratch, is_drawable};
+use glam::Mat3;
+use std::ops::Range;
+use vectorcade_shared::draw::{DrawCmd, Line2};
+
+#[cfg(feature = "fonts")]
+use vectorcade_fonts::{AtariMini, Cinematronics, FontRegistry, Midway, VectorScanline};
+
+/// A run of consecutive draws of one kind, in display-list order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Batch {
+    /// Indices into `Geometry::indices`.
+    Mesh(Range<u32>),
+    /// Instances in `CommandTessellator::lines`.
+    Lines(Range<u32>),
+}
+
+/// Turns `DrawCmd` lists into tessellated geometry.
+pub struct CommandTessellator {
+    pub state: RenderState,
+    pub geometry: Geometry,
+    /// Line segments for the instanced line pipeline.
+    pub lines: Vec<LineInstance>,
+    /// Draw order of `geometry` and `lines`.
+    pub batches: Vec<Batch>,
+    /// Emit `DrawCmd::Line` as [`LineInstance`]s instead of tessellating it.
+    pub instanced_lines: bool,
+    /// Local-space polyline meshes reused across frames.
+    pub cache: TessellationCache,
+    /// Lyon tessellator and buffers reused across strokes and frames.
+    scratch: StrokeScratch,
+    /// Tessellate large display lists on rayon's thread pool.
+    #[cfg(feature = "parallel")]
+    pub parallel: bool,
+    #[cfg(feature = "fonts")]
+    fonts: FontRegistry,
+}
+
+impl CommandTessellator {
+    /// Create a tessellator with the built-in vector fonts registered.
+    pub fn new() -> Self {
+        Self {
+            state: RenderState::default(),
+            geometry: Geometry::new(),
+            lines: Vec::new(),
+            batches: Vec::new(),
+            instanced_lines: false,
+            cache: TessellationCache::default(),
+            scratch: StrokeScratch::new(),
+            #[cfg(feature = "parallel")]
+            parallel: true,
+            #[cfg(feature = "fonts")]
+            fonts: builtin_fonts(),
+        }
+    }
+
+    /// Reset state and tessellate one frame of commands into `self.geometry`.
+    ///
+    /// `px_to_ndc` converts pixel measurements to NDC units (typically `2.0 / viewport_height`).
+    /// Points are projected with the projection set on `self.state`.
+    pub fn tessellate(&mut self, cmds: &[DrawCmd], px_to_ndc: f32) -> RenderStats {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = std::time::Instant::now();
+        let mut stats = RenderStats::default();
+        self.state.reset();
+        self.geometry.clear();
+        self.geometry.projection_scale = self.state.projection_scale();
+        self.lines.clear();
+        self.batches.clear();
+
+        self.tessellate_cmds(cmds, px_to_ndc, &mut stats);
+        self.cache.end_frame();
+        stats.vertices = self.geometry.vertices.len() as u32;
+        stats.indices = self.geometry.indices.len() as u32;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            stats.tessellation_time = start.elapsed();
+        }
+        stats
+    }
+
+    fn tessellate_cmds(&mut self, cmds: &[DrawCmd], px_to_ndc: f32, stats: &mut RenderStats) {
+        #[cfg(feature = "parallel")]
+        if self.parallel && cmds.len() >= parallel::MIN_COMMANDS {
+            self.tessellate_parallel(cmds, px_to_ndc, stats);
+            return;
+        }
+
+        for cmd in cmds {
+            let mesh_start = self.geometry.indices.len() as u32;
+            let lines_start = self.lines.len() as u32;
+            self.tessellate_cmd(cmd, px_to_ndc, stats);
+            push_batch(&mut self.batches, Batch::Mesh(mesh_start..self.geometry.indices.len() as u32));
+            push_batch(&mut self.batches, Batch::Lines(lines_start..self.lines.len() as u32));
+        }
+    }
+
+    fn tessellate_cmd(&mut self, cmd: &DrawCmd, px_to_ndc: f32, stats: &mut RenderStats) {
+        match cmd {
+            DrawCmd::Clear { color } => self.state.clear = *color,
+            DrawCmd::PushTransform(t) => self.state.push(*t),
+            DrawCmd::PopTransform => self.state.pop(),
+            DrawCmd::Line(line) => {
+                let t = self.state.transform_opt();
+                let (scratch, geometry, lines) = (&mut self.scratch, &mut self.geometry, &mut self.lines);
+                let outcome = draw_line(line, t.as_ref(), px_to_ndc, self.instanced_lines, scratch, geometry, lines);
+                count_outcome(stats, outcome);
+                stats.lines += 1;
+            }
+            DrawCmd::Polyline {
+                pts,
+                closed,
+                stroke,
+            } => {
+                let t = self.state.transform_opt();
+                let geometry = &mut self.geometry;
+                let lookup = self.cache.tessellate_polyline(pts, *closed, stroke, t.as_ref(), px_to_ndc, geometry);
+                count_lookup(stats, lookup);
+                stats.polylines += 1;
+            }
+            DrawCmd::Text { .. } => {
+                #[cfg(feature = "fonts")]
+                {
+                    let (t, scratch) = (self.state.transform_opt(), &mut self.scratch);
+                    let scale = self.state.projection_scale();
+                    let geometry = &mut self.geometry;
+                    stats.failed += draw_text(&self.fonts, cmd, t.as_ref(), px_to_ndc, scale, scratch, geometry);
+                }
+                stats.text_runs += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Tessellate `line`, or add it as an instance when `instanced`.
+fn draw_line(
+    line: &Line2,
+    transform: Option<&Mat3>,
+    px_to_ndc: f32,
+    instanced: bool,
+    scratch: &mut StrokeScratch,
+    geometry: &mut Geometry,
+    lines: &mut Vec<LineInstance>,
+) -> StrokeOutcome {
+    if !instanced {
+        return scratch.line(line, transform, px_to_ndc, geometry);
+    }
+    if !is_drawable(&[line.a, line.b]) {
+        return StrokeOutcome::Skipped;
+    }
+    lines.push(LineInstance::new(line, transform));
+    StrokeOutcome::Tessellated
+}
+
+/// Count a stroke that was skipped or failed.
+fn count_outcome(stats: &mut RenderStats, outcome: StrokeOutcome) {
+    match outcome {
+        StrokeOutcome::Tessellated => {}
+        StrokeOutcome::Skipped => stats.skipped += 1,
+        StrokeOutcome::Failed => stats.failed += 1,
+    }
+}
+
+/// Count a polyline by how the cache produced it.
+fn count_lookup(stats: &mut RenderStats, lookup: Lookup) {
+    match lookup {
+        Lookup::Hit => stats.cache_hits += 1,
+        Lookup::Miss => stats.cache_misses += 1,
+        Lookup::Skipped => stats.skipped += 1,
+        Lookup::Failed => stats.failed += 1,
+    }
+}
+
+/// Tessellate the glyphs of a `DrawCmd::Text`, returning the number of failed glyph paths.
+#[cfg(feature = "fonts")]
+fn draw_text(
+    fonts: &FontRegistry,
+    cmd: &DrawCmd,
+    transform: Option<&Mat3>,
+    px_to_ndc: f32,
+    projection_scale: glam::Vec2,
+    scratch: &mut StrokeScratch,
+    geometry: &mut Geometry,
+) -> u32 {
+    let DrawCmd::Text { pos, text, size_px, color, style } = cmd else { return 0 };
+    let params = crate::text::TextParams {
+        registry: fonts, text, pos: *pos, size_px: *size_px, color: *color, style: *style, px_to_ndc,
+        projection_scale,
+    };
+    crate::text::tessellate_text(&params, transform, scratch, geometry)
+}
+
+/// Append a non-empty batch, merging it into the previous one if contiguous.
+fn push_batch(batches: &mut Vec<Batch>, batch: Batch) {
+    let merged = match (batches.last_mut(), &batch) {
+        (_, Batch::Mesh(r) | Batch::Lines(r)) if r.is_empty() => true,
+        (Some(Batch::Mesh(last)), Batch::Mesh(r)) | (Some(Batch::Lines(last)), Batch::Lines(r))
+            if last.end == r.start =>
+        {
+            last.end = r.end;
+            true
+        }
+        _ => false,
+    };
+    if !merged {
+        batches.push(batch);
+    }
+}
+
+impl Default for CommandTessellator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "fonts")]
+pub(crate) fn builtin_fonts() -> FontRegistry {
+    let mut fonts = FontRegistry::new();
+    fonts.register(AtariMini);
+    fonts.register(Cinematronics);
+    fonts.register(Midway);
+    fonts.register(VectorScanline);
+    fonts
+}
@@ -0,0 +1,136 @@
+//! Parallel tessellation of large display lists (feature `parallel`).
+//!
+//! `Clear` and the transform stack are resolved serially into a list of draw
+//! items; text is tessellated during that pass, as the font registry is not
+//! shared across threads. The items are split into fixed-size chunks that
+//! rayon tessellates into per-chunk geometry, and the chunks are merged in
+//! display-list order, so the output does not depend on scheduling. Chunks
+//! only read the tessellation cache and record their hits and new meshes,
+//! which are applied while merging.
+
+use super::{Batch, CommandTessellator, count_lookup, count_outcome, draw_line, push_batch};
+use crate::RenderStats;
+use crate::cache::{CacheLog, TessellationCache};
+use crate::tessellate::{Geometry, LineInstance, StrokeScratch};
+use glam::{Mat3, Vec2};
+use rayon::prelude::*;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+
+/// Display lists with at least this many commands are tessellated in parallel.
+pub const MIN_COMMANDS: usize = 1024;
+
+/// Draw items per chunk. Fixed so the result does not depend on the thread count.
+const CHUNK_ITEMS: usize = 256;
+
+/// A draw command with its resolved transform.
+enum Item<'a> {
+    Line(&'a Line2, Option<Mat3>),
+    Polyline { pts: &'a [Vec2], closed: bool, stroke: &'a Stroke, transform: Option<Mat3> },
+    /// Text, already tessellated.
+    Text(Geometry),
+}
+
+/// Output of one chunk, with batch ranges relative to the chunk.
+#[derive(Default)]
+struct Chunk {
+    geometry: Geometry,
+    lines: Vec<LineInstance>,
+    batches: Vec<Batch>,
+    stats: RenderStats,
+    log: CacheLog,
+}
+
+impl CommandTessellator {
+    /// Tessellate `cmds` on rayon's thread pool into `self.geometry`.
+    pub(super) fn tessellate_parallel(&mut self, cmds: &[DrawCmd], px_to_ndc: f32, stats: &mut RenderStats) {
+        let items = self.resolve(cmds, px_to_ndc, stats);
+        let (cache, instanced) = (&self.cache, self.instanced_lines);
+        let chunks: Vec<Chunk> = items
+            .par_chunks(CHUNK_ITEMS)
+            .map(|items| tessellate_chunk(items, cache, px_to_ndc, instanced))
+            .collect();
+        for chunk in chunks {
+            self.merge(chunk, stats);
+        }
+    }
+
+    /// Walk the transform stack, returning the draws with their transforms.
+    #[cfg_attr(not(feature = "fonts"), allow(unused_variables))]
+    fn resolve<'a>(&mut self, cmds: &'a [DrawCmd], px_to_ndc: f32, stats: &mut RenderStats) -> Vec<Item<'a>> {
+        let mut items = Vec::with_capacity(cmds.len());
+        for cmd in cmds {
+            let transform = self.state.transform_opt();
+            match cmd {
+                DrawCmd::Clear { color } => self.state.clear = *color,
+                DrawCmd::PushTransform(t) => self.state.push(*t),
+                DrawCmd::PopTransform => self.state.pop(),
+                DrawCmd::Line(line) => items.push(Item::Line(line, transform)),
+                DrawCmd::Polyline { pts, closed, stroke } => {
+                    items.push(Item::Polyline { pts, closed: *closed, stroke, transform });
+                }
+                DrawCmd::Text { .. } => {
+                    #[cfg(feature = "fonts")]
+                    let geometry = {
+                        let mut geometry = Geometry::new();
+                        let (t, scratch) = (transform.as_ref(), &mut self.scratch);
+                        let scale = self.state.projection_scale();
+                        stats.failed += super::draw_text(&self.fonts, cmd, t, px_to_ndc, scale, scratch, &mut geometry);
+                        geometry
+                    };
+                    #[cfg(not(feature = "fonts"))]
+                    let geometry = Geometry::new();
+                    items.push(Item::Text(geometry));
+                    stats.text_runs += 1;
+                }
+                _ => {}
+            }
+        }
+        items
+    }
+
+    /// Append a chunk, offsetting its batches past the geometry and lines so far.
+    fn merge(&mut self, chunk: Chunk, stats: &mut RenderStats) {
+        let index_base = self.geometry.indices.len() as u32;
+        let line_base = self.lines.len() as u32;
+        self.geometry.extend_from(&chunk.geometry);
+        self.lines.extend_from_slice(&chunk.lines);
+        for batch in chunk.batches {
+            let batch = match batch {
+                Batch::Mesh(r) => Batch::Mesh(r.start + index_base..r.end + index_base),
+                Batch::Lines(r) => Batch::Lines(r.start + line_base..r.end + line_base),
+            };
+            push_batch(&mut self.batches, batch);
+        }
+        *stats += chunk.stats;
+        self.cache.commit(chunk.log);
+    }
+}
+
+fn tessellate_chunk(items: &[Item<'_>], cache: &TessellationCache, px_to_ndc: f32, instanced: bool) -> Chunk {
+    let mut chunk = Chunk::default();
+    let mut scratch = StrokeScratch::new();
+    for item in items {
+        let mesh_start = chunk.geometry.indices.len() as u32;
+        let lines_start = chunk.lines.len() as u32;
+        match item {
+            Item::Line(line, t) => {
+                let (geometry, lines) = (&mut chunk.geometry, &mut chunk.lines);
+                let outcome = draw_line(line, t.as_ref(), px_to_ndc, instanced, &mut scratch, geometry, lines);
+                count_outcome(&mut chunk.stats, outcome);
+                chunk.stats.lines += 1;
+            }
+            Item::Polyline { pts, closed, stroke, transform } => {
+                let (geometry, log) = (&mut chunk.geometry, &mut chunk.log);
+                let t = transform.as_ref();
+                let lookup =
+                    cache.tessellate_polyline_shared(pts, *closed, stroke, t, px_to_ndc, &mut scratch, geometry, log);
+                count_lookup(&mut chunk.stats, lookup);
+                chunk.stats.polylines += 1;
+            }
+            Item::Text(geometry) => chunk.geometry.extend_from(geometry),
+        }
+        push_batch(&mut chunk.batches, Batch::Mesh(mesh_start..chunk.geometry.indices.len() as u32));
+        push_batch(&mut chunk.batches, Batch::Lines(lines_start..chunk.lines.len() as u32));
+    }
+    chunk
+}
@@ -0,0 +1,57 @@
+//! World coordinate conventions, mapped to NDC by a projection matrix.
+//!
+//! The projection sits beneath the transform stack: every frame starts from
+//! it, and `PushTransform`s compose on top of it. Stroke widths and text
+//! sizes are in pixels in every convention.
+
+use glam::{Mat3, Vec2};
+
+/// Which way increasing y points on screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum YAxis {
+    #[default]
+    Up,
+    Down,
+}
+
+/// Coordinate convention of the points in a display list.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CoordinateSystem {
+    /// [-1, 1] on both axes, covering the viewport; the classic convention.
+    Normalized(YAxis),
+    /// Viewport pixels, with the origin at the bottom-left (`Up`) or top-left (`Down`) corner.
+    Pixels(YAxis),
+    /// The rectangle from `min` to `max` covers the viewport; `min.y` is
+    /// at the bottom edge for `YAxis::Up` and at the top edge for `Down`.
+    World { min: Vec2, max: Vec2, y_axis: YAxis },
+}
+
+impl Default for CoordinateSystem {
+    fn default() -> Self {
+        Self::Normalized(YAxis::Up)
+    }
+}
+
+impl CoordinateSystem {
+    /// Matrix mapping world points to NDC for a `width`x`height` pixel viewport.
+    ///
+    /// Empty or non-finite world rectangles fall back to the identity.
+    #[must_use]
+    pub fn projection(self, width: u32, height: u32) -> Mat3 {
+        let (min, max, y_axis) = match self {
+            Self::Normalized(y_axis) => (Vec2::NEG_ONE, Vec2::ONE, y_axis),
+            Self::Pixels(y_axis) => (Vec2::ZERO, Vec2::new(width as f32, height as f32), y_axis),
+            Self::World { min, max, y_axis } => (min, max, y_axis),
+        };
+        let size = max - min;
+        if !(size.is_finite() && size.x != 0.0 && size.y != 0.0) {
+            return Mat3::IDENTITY;
+        }
+        let flip = match y_axis {
+            YAxis::Up => Vec2::ONE,
+            YAxis::Down => Vec2::new(1.0, -1.0),
+        };
+        let scale = 2.0 / size * flip;
+        Mat3::from_translation(-(min + max) / size * flip) * Mat3::from_scale(scale)
+    }
+}
@@ -0,0 +1,103 @@
+//! RGBA8 images and PNG encoding/decoding.
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// An 8-bit RGBA image with tightly packed rows, top row first.
+///
+/// Color values are sRGB-encoded, as they would appear on screen.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Create a transparent black image.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height, pixels: vec![0; (width * height * 4) as usize] }
+    }
+
+    /// Wrap existing pixel data, or `None` if its length does not match the size.
+    #[must_use]
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<u8>) -> Option<Self> {
+        (pixels.len() == (width * height * 4) as usize).then_some(Self { width, height, pixels })
+    }
+
+    /// RGBA value of the pixel at `(x, y)`.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside the image.
+    #[must_use]
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        assert!(x < self.width && y < self.height, "pixel ({x}, {y}) out of bounds");
+        let i = ((y * self.width + x) * 4) as usize;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    /// Encode the image as an sRGB PNG into `writer`.
+    ///
+    /// # Errors
+    /// Returns an error if encoding or writing fails.
+    pub fn write_png<W: Write>(&self, writer: W) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()
+    }
+
+    /// Encode the image as a PNG file at `path`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be created or encoding fails.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), png::EncodingError> {
+        let file = std::fs::File::create(path)?;
+        self.write_png(std::io::BufWriter::new(file))
+    }
+
+    /// Decode a PNG from `reader`, converting any 8- or 16-bit color type to RGBA8.
+    ///
+    /// # Errors
+    /// Returns an error if the data is not a valid PNG.
+    pub fn read_png<R: Read>(reader: R) -> Result<Self, png::DecodingError> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+        buf.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buf,
+            png::ColorType::Rgb => buf.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+            png::ColorType::GrayscaleAlpha => buf.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+            _ => buf.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        };
+        Ok(Self { width: info.width, height: info.height, pixels })
+    }
+
+    /// Decode the PNG file at `path`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be opened or is not a valid PNG.
+    pub fn load_png(path: impl AsRef<Path>) -> Result<Self, png::DecodingError> {
+        let file = std::fs::File::open(path)?;
+        Self::read_png(std::io::BufReader::new(file))
+    }
+}
+
+/// Encode a linear color channel as an sRGB byte.
+pub(crate) fn srgb_u8(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    unorm_u8(if c <= 0.003_130_8 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 })
+}
+
+/// Encode a `0.0..=1.0` value (e.g. alpha) as a byte.
+pub(crate) fn unorm_u8(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
@@ -7,51 +7,85 @@
 //! # Features
 //!
 //! - **Line tessellation** via lyon with round caps/joins
-//! - **Transform stack** for hierarchical rendering
+//! - **Allocation-free frames** once scratch buffers have warmed up
+//! - **Tessellation cache** reusing polyline meshes across frames and transforms
+//! - **Parallel tessellation** of large display lists on rayon (feature `parallel`)
+//! - **Retained meshes** for static shapes, drawn instanced with a transform and tint
+//! - **Transform stack** for hierarchical rendering, applied in the vertex shader
+//! - **Coordinate systems**: normalized, pixel or world-rectangle space, y-up or y-down, via `CoordinateSystem`
 //! - **Text rendering** via vectorcade-fonts (Atari, Cinematronics, Midway styles)
-//! - **4x MSAA** anti-aliasing for smooth edges
+//! - **Renderer configuration** of present mode, frame latency and adapter via `RendererConfig`
+//! - **MSAA** anti-aliasing (1x-16x, default 4x) with capability fallback
+//! - **Instanced lines** with analytic anti-aliasing (opt-in via `set_instanced_lines`)
+//! - **Phosphor glow** bloom driven by each stroke's `glow` amount
+//! - **Phosphor persistence** with per-channel afterimage decay
 //! - **WASM compatible** for WebGPU in browsers
+//! - **Headless rendering** into an owned texture, with software adapter fallback
+//! - **Shared GPU context** via `from_device`, reusing a host application's wgpu device and queue
+//! - **Viewport mapping** with letterbox/pillarbox bars or cropping via `ViewportMode`
+//! - **Embedding** into a host's frame via `render_to`, recording into its encoder and texture view
+//! - **Render statistics** (geometry, uploads, draw calls, timing) with rolling averages
+//! - **GPU profiling** of each render pass via timestamp queries, when supported
+//! - **Typed errors** (`RenderError`) for device setup and surface loss, with fallible `try_render`
+//! - **Screenshots** via `capture_frame` and PNG encoding
+//! - **Software rendering** on machines without a GPU via `SoftwareRenderer`
+//! - **SVG export** of frames for vector editors via `SvgRenderer`
+//! - **Recording and replay** of display lists via `RecordingRenderer` and `Replayer`
 //!
 //! # Usage
 //!
 //! ```ignore
-//! use vectorcade_render_wgpu::{WgpuRenderer, VectorRenderer};
+//! use vectorcade_render_wgpu::{RendererConfig, WgpuRenderer, VectorRenderer};
 //!
 //! // Create renderer (requires window handle)
-//! let mut renderer = WgpuRenderer::new(window, width, height).await?;
+//! let mut renderer = WgpuRenderer::new(window, width, height, RendererConfig::default()).await?;
 //!
 //! // Render draw commands from a game
 //! let stats = renderer.render(&draw_commands);
 //! println!("Rendered {} lines, {} polylines", stats.lines, stats.polylines);
+//!
+//! // Or render without a window, e.g. in CI or server-side jobs
+//! let mut headless = WgpuRenderer::new_headless(640, 480).await?;
+//! headless.render(&draw_commands);
+//! let texture = headless.target_texture();
 //! ```
 //!
 //! # Feature Flags
 //!
-//! - `wgpu-backend` - Enables GPU rendering via wgpu (includes vectorcade-fonts)
+//! - `fonts` - Enables text rendering via vectorcade-fonts
+//! - `wgpu-backend` - Enables GPU rendering via wgpu (includes `fonts`)
+//! - `parallel` - Tessellates large display lists on multiple threads via rayon
+//! - `cli` - Builds the `vectorcade-render` command-line tool (includes `wgpu-backend`)
 //!
 //! # Modules
 //!
+//! - [`image`] - RGBA8 images and PNG encoding
+//! - [`record`] - Display-list recording and replay
+//! - [`software`] - CPU rasterizer producing deterministic images
+//! - [`svg`] - SVG document export
 //! - [`tessellate`] - Line tessellation using lyon
+//! - [`testing`] - Golden-image regression testing
 //! - [`wgpu_backend`] - GPU renderer (requires `wgpu-backend` feature)
 
+mod cache;
+mod commands;
+mod coords;
+pub mod image;
+pub mod record;
+pub mod software;
+mod state;
+mod stats;
+pub mod svg;
 pub mod tessellate;
+pub mod testing;
+#[cfg(feature = "fonts")]
+mod text;
 
 #[cfg(feature = "wgpu-backend")]
 pub mod wgpu_backend;
 
 use vectorcade_shared::draw::DrawCmd;
 
-/// Statistics about rendered primitives.
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-pub struct RenderStats {
-    /// Number of individual line segments rendered.
-    pub lines: u32,
-    /// Number of polyline paths rendered.
-    pub polylines: u32,
-    /// Number of text runs rendered.
-    pub text_runs: u32,
-}
-
 /// Trait for vector graphics renderers.
 ///
 /// Implementations consume `DrawCmd` display-lists and produce rendered output.
@@ -80,7 +114,15 @@ impl VectorRenderer for NullRenderer {
     }
 }
 
-pub use tessellate::{Geometry, Vertex};
+pub use coords::{CoordinateSystem, YAxis};
+pub use image::RgbaImage;
+pub use record::{RecordingRenderer, Replayer};
+pub use software::SoftwareRenderer;
+pub use stats::{RenderStats, RollingStats};
+pub use svg::SvgRenderer;
+pub use tessellate::{Geometry, LineInstance, Vertex};
 
 #[cfg(feature = "wgpu-backend")]
-pub use wgpu_backend::WgpuRenderer;
+pub use wgpu_backend::{
+    GpuTimings, LoadMode, MeshId, RenderError, RendererConfig, ViewportMode, ViewportRect, WgpuRenderer,
+};
@@ -0,0 +1,233 @@
+//! Binary encoding of recorded display lists.
+//!
+//! All values are little-endian. A file is a header followed by frames:
+//!
+//! ```text
+//! header: "VCDL" magic, u16 version, u32 width, u32 height
+//! frame:  b'F', u64 timestamp (microseconds), u32 command count, commands
+//! cmd:    u8 tag, payload
+//! ```
+//!
+//! Colors are four `f32`s, points two `f32`s and strokes a color plus
+//! `width_px` and `glow`. Strings and point lists carry a `u32` length.
+
+use glam::{Mat3, Vec2};
+use std::io::{self, Read};
+use std::time::Duration;
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+use vectorcade_shared::font::FontStyleId;
+
+/// File signature.
+pub const MAGIC: [u8; 4] = *b"VCDL";
+/// Current format version.
+pub const VERSION: u16 = 1;
+
+const FRAME_MARKER: u8 = b'F';
+
+const TAG_CLEAR: u8 = 0;
+const TAG_LINE: u8 = 1;
+const TAG_POLYLINE: u8 = 2;
+const TAG_TEXT: u8 = 3;
+const TAG_PUSH_TRANSFORM: u8 = 4;
+const TAG_POP_TRANSFORM: u8 = 5;
+
+/// Lists longer than this are rejected as corrupt instead of allocated.
+const MAX_LEN: u32 = 1 << 24;
+
+/// Append the file header.
+pub fn write_header(out: &mut Vec<u8>, width: u32, height: u32) {
+    out.extend_from_slice(&MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+}
+
+/// Read and validate the file header, returning `(version, width, height)`.
+pub fn read_header(r: &mut impl Read) -> io::Result<(u16, u32, u32)> {
+    let mut magic = [0; 4];
+    r.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(invalid("not a display-list recording"));
+    }
+    let version = u16::from_le_bytes(read_array(r)?);
+    if version == 0 || version > VERSION {
+        return Err(invalid(&format!("unsupported recording version {version}")));
+    }
+    Ok((version, read_u32(r)?, read_u32(r)?))
+}
+
+/// Append one frame. Commands this format cannot represent are skipped.
+///
+/// # Errors
+/// Returns `InvalidInput` if a string or point list is longer than a reader
+/// accepts; `out` then holds a partial frame.
+pub fn write_frame(out: &mut Vec<u8>, timestamp: Duration, cmds: &[DrawCmd]) -> io::Result<()> {
+    out.push(FRAME_MARKER);
+    let micros = u64::try_from(timestamp.as_micros()).unwrap_or(u64::MAX);
+    out.extend_from_slice(&micros.to_le_bytes());
+    let count_at = out.len();
+    out.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut count = 0u32;
+    for cmd in cmds {
+        if write_cmd(out, cmd)? {
+            count += 1;
+        }
+    }
+    out[count_at..count_at + 4].copy_from_slice(&count.to_le_bytes());
+    Ok(())
+}
+
+/// Read the next frame, or `None` at a clean end of file.
+pub fn read_frame(r: &mut impl Read) -> io::Result<Option<(Duration, Vec<DrawCmd>)>> {
+    let marker = match read_array(r) {
+        Ok([marker]) => marker,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if marker != FRAME_MARKER {
+        return Err(invalid("missing frame marker"));
+    }
+    let timestamp = Duration::from_micros(u64::from_le_bytes(read_array(r)?));
+    let count = read_len(r)?;
+    let mut cmds = Vec::with_capacity(count.min(4096) as usize);
+    for _ in 0..count {
+        cmds.push(read_cmd(r)?);
+    }
+    Ok(Some((timestamp, cmds)))
+}
+
+fn write_cmd(out: &mut Vec<u8>, cmd: &DrawCmd) -> io::Result<bool> {
+    match cmd {
+        DrawCmd::Clear { color } => {
+            out.push(TAG_CLEAR);
+            put_rgba(out, *color);
+        }
+        DrawCmd::Line(line) => {
+            out.push(TAG_LINE);
+            put_f32s(out, &[line.a.x, line.a.y, line.b.x, line.b.y]);
+            put_stroke(out, &line.stroke);
+        }
+        DrawCmd::Polyline { pts, closed, stroke } => {
+            out.push(TAG_POLYLINE);
+            out.push(u8::from(*closed));
+            put_len(out, pts.len())?;
+            pts.iter().for_each(|p| put_f32s(out, &[p.x, p.y]));
+            put_stroke(out, stroke);
+        }
+        DrawCmd::Text { pos, text, size_px, color, style } => {
+            out.push(TAG_TEXT);
+            put_f32s(out, &[pos.x, pos.y, *size_px]);
+            put_rgba(out, *color);
+            out.extend_from_slice(&style.0.to_le_bytes());
+            put_len(out, text.len())?;
+            out.extend_from_slice(text.as_bytes());
+        }
+        DrawCmd::PushTransform(t) => {
+            out.push(TAG_PUSH_TRANSFORM);
+            put_f32s(out, &t.to_cols_array());
+        }
+        DrawCmd::PopTransform => out.push(TAG_POP_TRANSFORM),
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn read_cmd(r: &mut impl Read) -> io::Result<DrawCmd> {
+    let [tag] = read_array(r)?;
+    Ok(match tag {
+        TAG_CLEAR => DrawCmd::Clear { color: read_rgba(r)? },
+        TAG_LINE => {
+            let (a, b) = (read_vec2(r)?, read_vec2(r)?);
+            DrawCmd::Line(Line2 { a, b, stroke: read_stroke(r)? })
+        }
+        TAG_POLYLINE => {
+            let [closed] = read_array(r)?;
+            let len = read_len(r)?;
+            let mut pts = Vec::with_capacity(len.min(4096) as usize);
+            for _ in 0..len {
+                pts.push(read_vec2(r)?);
+            }
+            DrawCmd::Polyline { pts, closed: closed != 0, stroke: read_stroke(r)? }
+        }
+        TAG_TEXT => {
+            let (pos, size_px, color) = (read_vec2(r)?, read_f32(r)?, read_rgba(r)?);
+            let style = FontStyleId(read_u32(r)?);
+            let mut bytes = vec![0; read_len(r)? as usize];
+            r.read_exact(&mut bytes)?;
+            let text = String::from_utf8(bytes).map_err(|_| invalid("text is not UTF-8"))?;
+            DrawCmd::Text { pos, text, size_px, color, style }
+        }
+        TAG_PUSH_TRANSFORM => {
+            let mut cols = [0.0; 9];
+            for c in &mut cols {
+                *c = read_f32(r)?;
+            }
+            DrawCmd::PushTransform(Mat3::from_cols_array(&cols))
+        }
+        TAG_POP_TRANSFORM => DrawCmd::PopTransform,
+        other => return Err(invalid(&format!("unknown command tag {other}"))),
+    })
+}
+
+fn put_f32s(out: &mut Vec<u8>, values: &[f32]) {
+    values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes()));
+}
+
+fn put_rgba(out: &mut Vec<u8>, c: Rgba) {
+    put_f32s(out, &[c.0, c.1, c.2, c.3]);
+}
+
+fn put_stroke(out: &mut Vec<u8>, stroke: &Stroke) {
+    put_rgba(out, stroke.color);
+    put_f32s(out, &[stroke.width_px, stroke.glow]);
+}
+
+fn put_len(out: &mut Vec<u8>, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).ok().filter(|&len| len <= MAX_LEN).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("length {len} exceeds limit of {MAX_LEN}"))
+    })?;
+    out.extend_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+fn read_array<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(read_array(r)?))
+}
+
+fn read_len(r: &mut impl Read) -> io::Result<u32> {
+    let len = read_u32(r)?;
+    if len > MAX_LEN {
+        return Err(invalid(&format!("length {len} exceeds limit")));
+    }
+    Ok(len)
+}
+
+fn read_f32(r: &mut impl Read) -> io::Result<f32> {
+    Ok(f32::from_le_bytes(read_array(r)?))
+}
+
+fn read_vec2(r: &mut impl Read) -> io::Result<Vec2> {
+    Ok(Vec2::new(read_f32(r)?, read_f32(r)?))
+}
+
+fn read_rgba(r: &mut impl Read) -> io::Result<Rgba> {
+    Ok(Rgba(read_f32(r)?, read_f32(r)?, read_f32(r)?, read_f32(r)?))
+}
+
+fn read_stroke(r: &mut impl Read) -> io::Result<Stroke> {
+    let color = read_rgba(r)?;
+    let (width_px, glow) = (read_f32(r)?, read_f32(r)?);
+    Ok(Stroke { color, width_px, glow })
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
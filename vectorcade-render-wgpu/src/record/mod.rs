@@ -0,0 +1,180 @@
+//! Display-list recording and replay.
+//!
+//! `RecordingRenderer` wraps any [`VectorRenderer`] and writes every frame it
+//! renders to a compact, versioned binary stream; `Replayer` reads such a
+//! stream back and feeds the frames to another renderer. This lets rendering
+//! bugs be reproduced without the game that produced them. The format is
+//! described in the `codec` module.
+
+mod codec;
+
+pub use codec::VERSION;
+
+use crate::{RenderStats, VectorRenderer};
+use std::io::{self, Read, Write};
+use std::time::Duration;
+use vectorcade_shared::draw::DrawCmd;
+
+/// Renderer wrapper that records every frame before rendering it.
+///
+/// Write errors do not interrupt rendering: recording stops at the first
+/// error, which [`finish`](Self::finish) reports. A string or point list too
+/// long to be read back is such an error, and its frame is not written.
+pub struct RecordingRenderer<R, W: Write> {
+    inner: R,
+    writer: W,
+    buffer: Vec<u8>,
+    error: Option<io::Error>,
+    frames: u64,
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+}
+
+impl<R: VectorRenderer, W: Write> RecordingRenderer<R, W> {
+    /// Start recording frames rendered by `inner` for a `width` x `height` viewport.
+    ///
+    /// # Errors
+    /// Returns an error if the header cannot be written.
+    pub fn new(inner: R, mut writer: W, width: u32, height: u32) -> io::Result<Self> {
+        let mut buffer = Vec::new();
+        codec::write_header(&mut buffer, width, height);
+        writer.write_all(&buffer)?;
+        Ok(Self {
+            inner,
+            writer,
+            buffer,
+            error: None,
+            frames: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+        })
+    }
+
+    /// Record `cmds` with an explicit timestamp, then render them.
+    ///
+    /// [`render`](VectorRenderer::render) timestamps frames with the time since
+    /// recording started; on wasm32, where no clock is available, it records
+    /// zero and this method should be used instead.
+    pub fn render_at(&mut self, cmds: &[DrawCmd], timestamp: Duration) -> RenderStats {
+        if self.error.is_none() {
+            self.buffer.clear();
+            let written = codec::write_frame(&mut self.buffer, timestamp, cmds);
+            match written.and_then(|()| self.writer.write_all(&self.buffer)) {
+                Ok(()) => self.frames += 1,
+                Err(e) => self.error = Some(e),
+            }
+        }
+        self.inner.render(cmds)
+    }
+
+    /// Number of frames recorded so far.
+    #[must_use]
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// The wrapped renderer.
+    pub fn inner_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Flush the recording and return the renderer and writer.
+    ///
+    /// # Errors
+    /// Returns the first write error encountered while recording, or a flush error.
+    pub fn finish(mut self) -> io::Result<(R, W)> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.writer.flush()?;
+        Ok((self.inner, self.writer))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+impl<R: VectorRenderer, W: Write> VectorRenderer for RecordingRenderer<R, W> {
+    fn render(&mut self, cmds: &[DrawCmd]) -> RenderStats {
+        let timestamp = self.elapsed();
+        self.render_at(cmds, timestamp)
+    }
+}
+
+/// A recorded frame.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    /// Time since the recording started.
+    pub timestamp: Duration,
+    /// The display list passed to `render`.
+    pub cmds: Vec<DrawCmd>,
+}
+
+/// Reads a recording and replays its frames.
+pub struct Replayer<Rd: Read> {
+    reader: Rd,
+    version: u16,
+    width: u32,
+    height: u32,
+}
+
+impl<Rd: Read> Replayer<Rd> {
+    /// Open a recording, reading and validating its header.
+    ///
+    /// # Errors
+    /// Returns an error if the header cannot be read, is not a recording, or
+    /// has an unsupported version.
+    pub fn new(mut reader: Rd) -> io::Result<Self> {
+        let (version, width, height) = codec::read_header(&mut reader)?;
+        Ok(Self { reader, version, width, height })
+    }
+
+    /// Format version of the recording.
+    #[must_use]
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Viewport size the recording was made at.
+    #[must_use]
+    pub fn viewport(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Read the next frame, or `None` at the end of the recording.
+    ///
+    /// # Errors
+    /// Returns an error if the frame is truncated or malformed.
+    pub fn next_frame(&mut self) -> io::Result<Option<Frame>> {
+        let frame = codec::read_frame(&mut self.reader)?;
+        Ok(frame.map(|(timestamp, cmds)| Frame { timestamp, cmds }))
+    }
+
+    /// Render the next frame into `renderer`, or return `None` at the end.
+    ///
+    /// # Errors
+    /// Returns an error if the frame is truncated or malformed.
+    pub fn replay_next(&mut self, renderer: &mut impl VectorRenderer) -> io::Result<Option<RenderStats>> {
+        Ok(self.next_frame()?.map(|frame| renderer.render(&frame.cmds)))
+    }
+
+    /// Render all remaining frames into `renderer` and return how many were replayed.
+    ///
+    /// # Errors
+    /// Returns an error if a frame is truncated or malformed; earlier frames
+    /// have already been rendered.
+    pub fn replay_all(&mut self, renderer: &mut impl VectorRenderer) -> io::Result<u64> {
+        let mut frames = 0;
+        while self.replay_next(renderer)?.is_some() {
+            frames += 1;
+        }
+        Ok(frames)
+    }
+}
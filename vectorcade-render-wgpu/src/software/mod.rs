@@ -0,0 +1,113 @@
+//! CPU software renderer for VectorCade.
+//!
+//! This module provides the `SoftwareRenderer`, which rasterizes the same
+//! tessellated geometry as `WgpuRenderer` into an RGBA image without a GPU.
+//! Output is deterministic, which makes it suitable for reference images.
+
+mod raster;
+
+use crate::commands::CommandTessellator;
+use crate::image::RgbaImage;
+use crate::{CoordinateSystem, RenderStats, VectorRenderer};
+use raster::SampleBuffer;
+use vectorcade_shared::draw::DrawCmd;
+
+/// Pure-Rust renderer producing an [`RgbaImage`].
+///
+/// Strokes and text are tessellated with lyon exactly as for the GPU and
+/// rasterized with 4x multisample coverage anti-aliasing. Phosphor glow and
+/// persistence are GPU-only and not applied.
+pub struct SoftwareRenderer {
+    commands: CommandTessellator,
+    coords: CoordinateSystem,
+    samples: SampleBuffer,
+    image: RgbaImage,
+}
+
+impl SoftwareRenderer {
+    /// Create a renderer with a `width` x `height` pixel buffer.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        Self {
+            commands: CommandTessellator::new(),
+            coords: CoordinateSystem::default(),
+            samples: SampleBuffer::new(width, height),
+            image: RgbaImage::new(width, height),
+        }
+    }
+
+    /// Resize the pixel buffer. The image is cleared until the next render.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.samples = SampleBuffer::new(width, height);
+            self.image = RgbaImage::new(width, height);
+        }
+    }
+
+    /// Current image size in pixels.
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        (self.image.width, self.image.height)
+    }
+
+    /// The most recently rendered frame.
+    #[must_use]
+    pub fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    /// Take ownership of the most recently rendered frame.
+    #[must_use]
+    pub fn into_image(self) -> RgbaImage {
+        self.image
+    }
+
+    /// Interpret display-list points in `coords`; `Pixels` are image pixels.
+    pub fn set_coordinate_system(&mut self, coords: CoordinateSystem) {
+        self.coords = coords;
+    }
+
+    /// Current coordinate convention of display lists.
+    #[must_use]
+    pub fn coordinate_system(&self) -> CoordinateSystem {
+        self.coords
+    }
+
+    /// Keep cached polyline meshes for `frames` frames after their last use; 0 disables the cache.
+    pub fn set_cache_max_age(&mut self, frames: u32) {
+        self.commands.cache.set_max_age(frames);
+    }
+
+    /// Tessellate display lists of 1024 or more commands on rayon's thread pool (default on).
+    #[cfg(feature = "parallel")]
+    pub fn set_parallel(&mut self, enabled: bool) {
+        self.commands.parallel = enabled;
+    }
+
+    fn rasterize(&mut self) {
+        let (width, height) = (self.image.width as f32, self.image.height as f32);
+        let to_px = |p: [f32; 2]| [(p[0] + 1.0) * 0.5 * width, (1.0 - p[1]) * 0.5 * height];
+
+        self.samples.clear(self.commands.state.background());
+        let geometry = &self.commands.geometry;
+        for tri in geometry.indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| geometry.vertices[i as usize]);
+            let points = [a, b, c].map(|v| to_px(geometry.resolve(&v)));
+            self.samples.fill_triangle(points, a.color);
+        }
+        self.samples.resolve(&mut self.image);
+    }
+}
+
+impl VectorRenderer for SoftwareRenderer {
+    fn render(&mut self, cmds: &[DrawCmd]) -> RenderStats {
+        let px_to_ndc = 2.0 / self.image.height as f32;
+        let projection = self.coords.projection(self.image.width, self.image.height);
+        self.commands.state.set_projection(projection);
+        let stats = self.commands.tessellate(cmds, px_to_ndc);
+        self.commands.geometry.set_target_size(self.image.width, self.image.height);
+        self.rasterize();
+        stats
+    }
+}
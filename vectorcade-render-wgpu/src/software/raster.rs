@@ -0,0 +1,119 @@
+//! Fixed-point triangle rasterizer with 4x multisample coverage.
+//!
+//! Vertices are snapped to 1/256 px and edge functions are evaluated in
+//! integers with a consistent tie-break rule, so triangles that share an edge
+//! never both cover (or both miss) a sample. Each sample is alpha-blended on
+//! its own and pixels are resolved by averaging, like GPU MSAA.
+
+use crate::image::{RgbaImage, srgb_u8, unorm_u8};
+
+/// Subpixel precision of vertex coordinates.
+const SUBPIXEL_BITS: u32 = 8;
+const ONE: i64 = 1 << SUBPIXEL_BITS;
+
+/// Triangles reaching further outside the image than this (in px) are skipped,
+/// which keeps the fixed-point edge products within `i64`.
+const MAX_COORD: f32 = (1 << 20) as f32;
+
+/// Standard 4x rotated-grid sample positions, in 1/16 px.
+const SAMPLE_OFFSETS: [(i64, i64); SAMPLES] = [(6, 2), (14, 6), (2, 10), (10, 14)];
+
+/// Number of coverage samples per pixel.
+pub const SAMPLES: usize = 4;
+
+/// Per-sample linear RGBA color storage.
+pub struct SampleBuffer {
+    width: u32,
+    height: u32,
+    samples: Vec<[f32; 4]>,
+}
+
+impl SampleBuffer {
+    /// Create a buffer of `width * height` pixels.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height, samples: vec![[0.0; 4]; (width * height) as usize * SAMPLES] }
+    }
+
+    /// Fill every sample with `color`.
+    pub fn clear(&mut self, color: [f32; 4]) {
+        self.samples.fill(color);
+    }
+
+    /// Alpha-blend a flat-colored triangle given in pixel coordinates (y down).
+    pub fn fill_triangle(&mut self, tri: [[f32; 2]; 3], color: [f32; 4]) {
+        if tri.iter().flatten().any(|c| !c.is_finite() || c.abs() > MAX_COORD) {
+            return;
+        }
+        let mut v = tri.map(|[x, y]| [to_fixed(x), to_fixed(y)]);
+        let area = edge(v[0], v[1], v[2]);
+        if area == 0 {
+            return;
+        }
+        if area < 0 {
+            v.swap(1, 2);
+        }
+
+        let min = |i: usize| v.iter().map(|p| p[i]).min().unwrap_or(0);
+        let max = |i: usize| v.iter().map(|p| p[i]).max().unwrap_or(0);
+        let x0 = (min(0) >> SUBPIXEL_BITS).max(0);
+        let y0 = (min(1) >> SUBPIXEL_BITS).max(0);
+        let x1 = (max(0) >> SUBPIXEL_BITS).min(i64::from(self.width) - 1);
+        let y1 = (max(1) >> SUBPIXEL_BITS).min(i64::from(self.height) - 1);
+        let edges = [(v[1], v[2]), (v[2], v[0]), (v[0], v[1])];
+
+        for py in y0..=y1 {
+            for px in x0..=x1 {
+                let base = (py as usize * self.width as usize + px as usize) * SAMPLES;
+                for (s, &(ox, oy)) in SAMPLE_OFFSETS.iter().enumerate() {
+                    let p = [px * ONE + ox * (ONE / 16), py * ONE + oy * (ONE / 16)];
+                    if edges.iter().all(|&(a, b)| covers(a, b, p)) {
+                        blend(&mut self.samples[base + s], color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Average the samples of each pixel into an sRGB-encoded image.
+    pub fn resolve(&self, image: &mut RgbaImage) {
+        image.width = self.width;
+        image.height = self.height;
+        image.pixels.clear();
+        for pixel in self.samples.chunks_exact(SAMPLES) {
+            let mut sum = [0.0f32; 4];
+            for sample in pixel {
+                for (acc, c) in sum.iter_mut().zip(sample) {
+                    *acc += c;
+                }
+            }
+            let avg = sum.map(|c| c / SAMPLES as f32);
+            image.pixels.extend_from_slice(&[srgb_u8(avg[0]), srgb_u8(avg[1]), srgb_u8(avg[2]), unorm_u8(avg[3])]);
+        }
+    }
+}
+
+fn to_fixed(v: f32) -> i64 {
+    (v * ONE as f32).round() as i64
+}
+
+/// Twice the signed area of `(a, b, p)`; positive when `p` is left of `a -> b` (y down).
+fn edge(a: [i64; 2], b: [i64; 2], p: [i64; 2]) -> i64 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// Whether sample `p` is inside edge `a -> b`.
+///
+/// Samples exactly on the edge belong to it only for one traversal direction,
+/// so the two triangles sharing an edge split those samples between them.
+fn covers(a: [i64; 2], b: [i64; 2], p: [i64; 2]) -> bool {
+    let e = edge(a, b, p);
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    e > 0 || (e == 0 && (dy > 0 || (dy == 0 && dx < 0)))
+}
+
+/// Source-over blend, matching `wgpu::BlendState::ALPHA_BLENDING`.
+fn blend(dst: &mut [f32; 4], src: [f32; 4]) {
+    let [r, g, b, a] = src;
+    let keep = 1.0 - a;
+    *dst = [r * a + dst[0] * keep, g * a + dst[1] * keep, b * a + dst[2] * keep, a + dst[3] * keep];
+}
@@ -1,6 +1,6 @@
 //! Render state for transform stack and clear color.
 
-use glam::Mat3;
+use glam::{Mat3, Vec2};
 use vectorcade_shared::Rgba;
 
 /// Render state for transform stack and clear color.
@@ -8,16 +8,28 @@ use vectorcade_shared::Rgba;
 pub struct RenderState {
     transform_stack: Vec<Mat3>,
     current: Mat3,
+    /// World-to-NDC matrix every frame starts from.
+    projection: Mat3,
     pub clear: Rgba,
 }
 
 impl RenderState {
     pub fn reset(&mut self) {
         self.transform_stack.clear();
-        self.current = Mat3::IDENTITY;
+        self.current = self.projection;
         self.clear = Rgba::BLACK;
     }
 
+    /// Set the projection beneath the transform stack, from the next `reset` on.
+    pub fn set_projection(&mut self, projection: Mat3) {
+        self.projection = projection;
+    }
+
+    /// Signed scale of the projection along x and y, for keeping sizes in pixels.
+    pub fn projection_scale(&self) -> Vec2 {
+        Vec2::new(self.projection.x_axis.x, self.projection.y_axis.y)
+    }
+
     pub fn push(&mut self, t: Mat3) {
         self.transform_stack.push(self.current);
         self.current *= t;
@@ -33,12 +45,7 @@ impl RenderState {
         (self.current != Mat3::IDENTITY).then_some(self.current)
     }
 
-    pub fn clear_color(&self) -> wgpu::Color {
-        wgpu::Color {
-            r: self.clear.0 as f64,
-            g: self.clear.1 as f64,
-            b: self.clear.2 as f64,
-            a: self.clear.3 as f64,
-        }
+    pub fn background(&self) -> [f32; 4] {
+        [self.clear.0, self.clear.1, self.clear.2, self.clear.3]
     }
 }
@@ -0,0 +1,178 @@
+//! Per-frame render statistics and rolling averages for performance overlays.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Statistics about rendered primitives.
+///
+/// Renderers fill in what applies to them: GPU-only counters such as
+/// `bytes_uploaded` and `draw_calls` stay zero for the CPU renderers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Number of individual line segments rendered.
+    pub lines: u32,
+    /// Number of polyline paths rendered.
+    pub polylines: u32,
+    /// Number of text runs rendered.
+    pub text_runs: u32,
+    /// Polylines whose mesh was reused from the tessellation cache.
+    pub cache_hits: u32,
+    /// Polylines tessellated from scratch.
+    pub cache_misses: u32,
+    /// Vertices in the frame's tessellated geometry.
+    pub vertices: u32,
+    /// Indices in the frame's tessellated geometry; see [`triangles`](Self::triangles).
+    pub indices: u32,
+    /// Bytes written to GPU vertex, index, instance and transform buffers.
+    pub bytes_uploaded: u64,
+    /// GPU buffers recreated because the frame outgrew them.
+    pub buffer_reallocations: u32,
+    /// Draw calls issued, including post-processing passes.
+    pub draw_calls: u32,
+    /// CPU time spent tessellating the display list. Zero on wasm32, which has no clock.
+    pub tessellation_time: Duration,
+    /// Strokes not tessellated because they had fewer than two points or non-finite coordinates.
+    pub skipped: u32,
+    /// Strokes lyon failed to tessellate.
+    pub failed: u32,
+    /// Times the surface was outdated, e.g. after a resize, and was reconfigured.
+    pub surface_outdated: u32,
+    /// Times the surface was lost, e.g. after a display change, and was reconfigured.
+    pub surface_lost: u32,
+    /// Frames skipped because acquiring the surface texture timed out.
+    pub surface_timeouts: u32,
+    /// Frames that failed because the GPU ran out of memory.
+    pub surface_out_of_memory: u32,
+}
+
+impl RenderStats {
+    /// Number of triangles in the frame's tessellated geometry.
+    #[must_use]
+    pub fn triangles(&self) -> u32 {
+        self.indices / 3
+    }
+
+    /// Add the counters of `other` to these, e.g. to total several render passes.
+    pub fn merge(&mut self, other: &Self) {
+        self.lines += other.lines;
+        self.polylines += other.polylines;
+        self.text_runs += other.text_runs;
+        self.cache_hits += other.cache_hits;
+        self.cache_misses += other.cache_misses;
+        self.vertices += other.vertices;
+        self.indices += other.indices;
+        self.bytes_uploaded += other.bytes_uploaded;
+        self.buffer_reallocations += other.buffer_reallocations;
+        self.draw_calls += other.draw_calls;
+        self.tessellation_time += other.tessellation_time;
+        self.skipped += other.skipped;
+        self.failed += other.failed;
+        self.surface_outdated += other.surface_outdated;
+        self.surface_lost += other.surface_lost;
+        self.surface_timeouts += other.surface_timeouts;
+        self.surface_out_of_memory += other.surface_out_of_memory;
+    }
+
+    /// Divide every counter by `n`, rounding down.
+    fn divided_by(mut self, n: u32) -> Self {
+        let n = n.max(1);
+        for field in [
+            &mut self.lines,
+            &mut self.polylines,
+            &mut self.text_runs,
+            &mut self.cache_hits,
+            &mut self.cache_misses,
+            &mut self.vertices,
+            &mut self.indices,
+            &mut self.buffer_reallocations,
+            &mut self.draw_calls,
+            &mut self.skipped,
+            &mut self.failed,
+            &mut self.surface_outdated,
+            &mut self.surface_lost,
+            &mut self.surface_timeouts,
+            &mut self.surface_out_of_memory,
+        ] {
+            *field /= n;
+        }
+        self.bytes_uploaded /= u64::from(n);
+        self.tessellation_time /= n;
+        self
+    }
+}
+
+impl std::ops::AddAssign for RenderStats {
+    fn add_assign(&mut self, other: Self) {
+        self.merge(&other);
+    }
+}
+
+/// Rolling average of the last few frames' [`RenderStats`].
+///
+/// Push each frame's stats after rendering; [`average`](Self::average) then
+/// smooths out per-frame jitter for display in an overlay.
+#[derive(Clone, Debug)]
+pub struct RollingStats {
+    frames: VecDeque<RenderStats>,
+    window: usize,
+}
+
+impl RollingStats {
+    /// Frames averaged by [`RollingStats::default`], one second at 60 Hz.
+    pub const DEFAULT_WINDOW: usize = 60;
+
+    /// Average over the last `window` frames (at least one).
+    #[must_use]
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self { frames: VecDeque::with_capacity(window), window }
+    }
+
+    /// Add a frame, dropping the oldest once the window is full.
+    pub fn push(&mut self, stats: RenderStats) {
+        if self.frames.len() == self.window {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(stats);
+    }
+
+    /// Number of frames currently averaged.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether no frames have been pushed yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Sum of the frames in the window.
+    #[must_use]
+    pub fn total(&self) -> RenderStats {
+        let mut total = RenderStats::default();
+        for stats in &self.frames {
+            total.merge(stats);
+        }
+        total
+    }
+
+    /// Per-frame average over the window; all zeros when empty.
+    #[must_use]
+    pub fn average(&self) -> RenderStats {
+        self.total().divided_by(self.frames.len() as u32)
+    }
+
+    /// The most recently pushed frame.
+    #[must_use]
+    pub fn latest(&self) -> Option<&RenderStats> {
+        self.frames.back()
+    }
+}
+
+impl Default for RollingStats {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_WINDOW)
+    }
+}
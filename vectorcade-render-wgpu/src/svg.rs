@@ -0,0 +1,216 @@
+//! SVG export of display lists.
+//!
+//! This module provides the `SvgRenderer`, which writes each frame as a
+//! standalone SVG document that opens in Inkscape and browsers. Strokes stay
+//! vectors: lines, polylines and polygons in pixel coordinates, with text
+//! expanded into glyph outlines.
+
+use crate::image::srgb_u8;
+use crate::state::RenderState;
+use crate::{CoordinateSystem, RenderStats, VectorRenderer};
+use glam::{Mat3, Vec3};
+use std::fmt::Write;
+use std::path::Path;
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Stroke};
+
+#[cfg(feature = "fonts")]
+use vectorcade_fonts::FontRegistry;
+
+/// Renderer producing an SVG document per frame.
+///
+/// Transforms are applied to the points, so stroke widths stay in pixels as
+/// on the GPU. Colors are written sRGB-encoded to match the rendered output;
+/// phosphor glow and persistence are not represented.
+pub struct SvgRenderer {
+    width: u32,
+    height: u32,
+    coords: CoordinateSystem,
+    state: RenderState,
+    #[cfg(feature = "fonts")]
+    fonts: FontRegistry,
+    body: String,
+    document: String,
+}
+
+impl SvgRenderer {
+    /// Create a renderer for a `width` x `height` pixel canvas.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width: width.max(1),
+            height: height.max(1),
+            coords: CoordinateSystem::default(),
+            state: RenderState::default(),
+            #[cfg(feature = "fonts")]
+            fonts: crate::commands::builtin_fonts(),
+            body: String::new(),
+            document: String::new(),
+        }
+    }
+
+    /// Resize the canvas used by subsequent renders.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.width = width;
+            self.height = height;
+        }
+    }
+
+    /// Current canvas size in pixels.
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Interpret display-list points in `coords`; `Pixels` are canvas pixels.
+    pub fn set_coordinate_system(&mut self, coords: CoordinateSystem) {
+        self.coords = coords;
+    }
+
+    /// Current coordinate convention of display lists.
+    #[must_use]
+    pub fn coordinate_system(&self) -> CoordinateSystem {
+        self.coords
+    }
+
+    /// The SVG document of the most recently rendered frame.
+    #[must_use]
+    pub fn document(&self) -> &str {
+        &self.document
+    }
+
+    /// Take ownership of the most recently rendered document.
+    #[must_use]
+    pub fn into_document(self) -> String {
+        self.document
+    }
+
+    /// Write the most recently rendered document to `path`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, &self.document)
+    }
+
+    fn draw(&mut self, cmd: &DrawCmd, stats: &mut RenderStats) {
+        match cmd {
+            DrawCmd::Clear { color } => self.state.clear = *color,
+            DrawCmd::PushTransform(t) => self.state.push(*t),
+            DrawCmd::PopTransform => self.state.pop(),
+            DrawCmd::Line(line) => {
+                let [a, b] = [line.a, line.b].map(|p| self.to_px([p.x, p.y]));
+                let (x1, y1, x2, y2) = (num(a[0]), num(a[1]), num(b[0]), num(b[1]));
+                let _ = write!(self.body, r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}""#);
+                self.write_stroke_attrs(&line.stroke);
+                stats.lines += 1;
+            }
+            DrawCmd::Polyline { pts, closed, stroke } => {
+                let pts: Vec<[f32; 2]> = pts.iter().map(|v| [v.x, v.y]).collect();
+                self.write_path(&pts, *closed, stroke);
+                stats.polylines += 1;
+            }
+            DrawCmd::Text { pos, text, size_px, color, style } => {
+                #[cfg(feature = "fonts")]
+                {
+                    let params = crate::text::TextParams {
+                        registry: &self.fonts, text, pos: *pos, size_px: *size_px, color: *color, style: *style,
+                        px_to_ndc: 2.0 / self.height as f32, projection_scale: self.state.projection_scale(),
+                    };
+                    let stroke = crate::text::text_stroke(&params);
+                    let mut paths = Vec::new();
+                    let mut buffer = Vec::new();
+                    crate::text::for_each_glyph_path(&params, &mut buffer, |pts, closed| {
+                        paths.push((pts.to_vec(), closed));
+                    });
+                    for (pts, closed) in paths {
+                        self.write_path(&pts, closed, &stroke);
+                    }
+                }
+                #[cfg(not(feature = "fonts"))]
+                let _ = (pos, text, size_px, color, style);
+                stats.text_runs += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// Emit a `<polyline>`, or a `<polygon>` when `closed`.
+    fn write_path(&mut self, pts: &[[f32; 2]], closed: bool, stroke: &Stroke) {
+        let tag = if closed { "polygon" } else { "polyline" };
+        let _ = write!(self.body, r#"<{tag} points=""#);
+        for (i, p) in pts.iter().enumerate() {
+            let [x, y] = self.to_px(*p);
+            let sep = if i == 0 { "" } else { " " };
+            let _ = write!(self.body, "{sep}{},{}", num(x), num(y));
+        }
+        self.body.push('"');
+        self.write_stroke_attrs(stroke);
+    }
+
+    /// Finish an element with the stroke color, opacity and width.
+    fn write_stroke_attrs(&mut self, stroke: &Stroke) {
+        let _ = write!(self.body, r#" stroke="{}" stroke-width="{}""#, hex(stroke.color), num(stroke.width_px));
+        if stroke.color.3 < 1.0 {
+            let _ = write!(self.body, r#" stroke-opacity="{}""#, num(stroke.color.3.max(0.0)));
+        }
+        self.body.push_str("/>\n");
+    }
+
+    /// Apply the current transform and map NDC to pixels (y down).
+    fn to_px(&self, p: [f32; 2]) -> [f32; 2] {
+        let [x, y] = match self.state.transform_opt() {
+            Some(t) => transform(&t, p),
+            None => p,
+        };
+        [(x + 1.0) * 0.5 * self.width as f32, (1.0 - y) * 0.5 * self.height as f32]
+    }
+
+    fn finish(&mut self) {
+        let (w, h) = (self.width, self.height);
+        let clear = self.state.clear;
+        self.document.clear();
+        let _ = writeln!(
+            self.document,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#
+        );
+        let _ = write!(self.document, r#"<rect width="{w}" height="{h}" fill="{}""#, hex(clear));
+        if clear.3 < 1.0 {
+            let _ = write!(self.document, r#" fill-opacity="{}""#, num(clear.3.max(0.0)));
+        }
+        self.document.push_str("/>\n");
+        self.document.push_str("<g fill=\"none\" stroke-linecap=\"round\" stroke-linejoin=\"round\">\n");
+        self.document.push_str(&self.body);
+        self.document.push_str("</g>\n</svg>\n");
+    }
+}
+
+impl VectorRenderer for SvgRenderer {
+    fn render(&mut self, cmds: &[DrawCmd]) -> RenderStats {
+        let mut stats = RenderStats::default();
+        self.state.set_projection(self.coords.projection(self.width, self.height));
+        self.state.reset();
+        self.body.clear();
+        for cmd in cmds {
+            self.draw(cmd, &mut stats);
+        }
+        self.finish();
+        stats
+    }
+}
+
+fn transform(t: &Mat3, p: [f32; 2]) -> [f32; 2] {
+    let r = *t * Vec3::new(p[0], p[1], 1.0);
+    [r.x, r.y]
+}
+
+/// `#rrggbb` for the sRGB encoding of a linear color.
+fn hex(c: Rgba) -> String {
+    format!("#{:02x}{:02x}{:02x}", srgb_u8(c.0), srgb_u8(c.1), srgb_u8(c.2))
+}
+
+/// Round to 1/1000 px so documents stay compact and diffable.
+fn num(v: f32) -> f32 {
+    (v * 1000.0).round() / 1000.0 + 0.0
+}
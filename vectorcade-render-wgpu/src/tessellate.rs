@@ -1,62 +1,311 @@
 //! Line and polyline tessellation using lyon.
 //!
 //! Converts vector primitives into triangle meshes for GPU rendering.
+//!
+//! Points are tessellated untransformed. Each vertex keeps its position on
+//! the stroke's centerline plus an offset to the stroke edge, and refers to a
+//! transform in [`Geometry::transforms`]. The vertex shader (or
+//! [`Geometry::resolve`] on the CPU) transforms the centerline point and adds
+//! the offset rotated but not scaled, so stroke widths stay in pixels and a
+//! tessellated shape can be reused under any transform.
 
-use glam::Mat3;
+use glam::{Mat2, Mat3, Vec2};
 use lyon::math::Point;
-use lyon::path::Path;
 use lyon::tessellation::{
-    BuffersBuilder, LineCap, LineJoin, StrokeOptions, StrokeTessellator, VertexBuffers,
+    BuffersBuilder, LineCap, LineJoin, StrokeOptions, StrokeTessellator, StrokeVertex, VertexBuffers,
 };
 use vectorcade_shared::draw::{Line2, Stroke};
 
-/// A vertex with position and color for GPU rendering.
+/// A vertex with position, color and glow amount for GPU rendering.
 #[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct Vertex {
+    /// Point on the stroke's centerline, before the transform.
     pub position: [f32; 2],
+    /// Offset from the centerline to this vertex in NDC, applied after the transform.
+    pub offset: [f32; 2],
+    pub color: [f32; 4],
+    /// Phosphor glow contribution, taken from `Stroke::glow`.
+    pub glow: f32,
+    /// Index into [`Geometry::transforms`].
+    pub transform: u32,
+}
+
+/// One line segment drawn by the instanced line pipeline.
+///
+/// The vertex shader expands each instance into a capsule-shaped quad and the
+/// fragment shader computes coverage from the distance to the segment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct LineInstance {
+    /// Start point in NDC, after transforms.
+    pub a: [f32; 2],
+    /// End point in NDC, after transforms.
+    pub b: [f32; 2],
+    /// Stroke width in pixels.
+    pub width_px: f32,
     pub color: [f32; 4],
+    /// Phosphor glow contribution, taken from `Stroke::glow`.
+    pub glow: f32,
+}
+
+impl LineInstance {
+    /// Build an instance from a line, applying `transform` to its endpoints.
+    #[must_use]
+    pub fn new(line: &Line2, transform: Option<&Mat3>) -> Self {
+        let xform = |p: glam::Vec2| match transform {
+            Some(t) => t.transform_point2(p).into(),
+            None => p.into(),
+        };
+        let c = line.stroke.color;
+        Self {
+            a: xform(line.a),
+            b: xform(line.b),
+            width_px: line.stroke.width_px,
+            color: [c.0, c.1, c.2, c.3],
+            glow: line.stroke.glow,
+        }
+    }
 }
 
 /// Tessellated geometry ready for GPU upload.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct Geometry {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    /// Transforms referenced by `Vertex::transform`; index 0 is the identity.
+    pub transforms: Vec<Mat3>,
+    /// Per-axis scale of the projection included in `transforms`, divided
+    /// out of the offsets so stroke widths stay in pixels.
+    pub projection_scale: Vec2,
+    /// Per-axis scale of the rotated offsets, `(height / width, 1)` for the
+    /// target, as offsets are tessellated in units of `2 / height`.
+    pub offset_scale: Vec2,
 }
 
 impl Geometry {
     /// Create empty geometry.
     #[must_use]
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            transforms: vec![Mat3::IDENTITY],
+            projection_scale: Vec2::ONE,
+            offset_scale: Vec2::ONE,
+        }
+    }
+
+    /// Scale offsets for a target of `width`x`height` pixels so strokes are
+    /// as wide horizontally as vertically.
+    pub fn set_target_size(&mut self, width: u32, height: u32) {
+        self.offset_scale = Vec2::new(height as f32 / width as f32, 1.0);
     }
 
-    /// Clear all vertices and indices.
+    /// Clear all vertices, indices and transforms but the identity.
     pub fn clear(&mut self) {
         self.vertices.clear();
         self.indices.clear();
+        self.transforms.truncate(1);
+    }
+
+    /// Index of `transform` in `transforms`, appending it unless it is the last one.
+    ///
+    /// `None` is the identity at index 0.
+    pub fn push_transform(&mut self, transform: Option<&Mat3>) -> u32 {
+        let Some(t) = transform else { return 0 };
+        if self.transforms.len() < 2 || self.transforms.last() != Some(t) {
+            self.transforms.push(*t);
+        }
+        (self.transforms.len() - 1) as u32
+    }
+
+    /// Append `other`, offsetting its indices and remapping its transforms.
+    pub fn extend_from(&mut self, other: &Geometry) {
+        let base_vertex = self.vertices.len() as u32;
+        let base_transform = self.transforms.len().saturating_sub(1) as u32;
+        self.transforms.extend(other.transforms.iter().skip(1));
+        self.vertices.extend(other.vertices.iter().map(|v| {
+            let transform = if v.transform == 0 { 0 } else { v.transform + base_transform };
+            Vertex { transform, ..*v }
+        }));
+        self.indices.extend(other.indices.iter().map(|i| i + base_vertex));
+    }
+
+    /// Final NDC position of `v`, as computed by the vertex shader.
+    #[must_use]
+    pub fn resolve(&self, v: &Vertex) -> [f32; 2] {
+        let t = self.transforms.get(v.transform as usize).unwrap_or(&Mat3::IDENTITY);
+        resolve_position(v, t, self.projection_scale, self.offset_scale)
+    }
+
+    /// `v` with its transform applied to the centerline point and its offset
+    /// rotated, leaving the offset for the shader to scale to the target.
+    #[cfg(feature = "wgpu-backend")]
+    #[must_use]
+    pub fn bake(&self, v: &Vertex) -> Vertex {
+        let t = self.transforms.get(v.transform as usize).unwrap_or(&Mat3::IDENTITY);
+        let position = t.transform_point2(Vec2::from(v.position)).into();
+        Vertex { position, offset: resolve_offset(v, t, self.projection_scale).into(), transform: 0, ..*v }
+    }
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Transform the centerline point of `v` by `t` and add its offset rotated by
+/// `t` with the scale divided out, so the stroke keeps its width in pixels.
+///
+/// `projection_scale` is removed from `t` before rotating the offset; it only
+/// flips it. For transforms that scale axes unequally the offset is
+/// normalized by the geometric mean of the scales. The rotated offset is
+/// then multiplied by `offset_scale`, the target's aspect correction.
+#[must_use]
+pub fn resolve_position(v: &Vertex, t: &Mat3, projection_scale: Vec2, offset_scale: Vec2) -> [f32; 2] {
+    let offset = resolve_offset(v, t, projection_scale) * offset_scale;
+    (t.transform_point2(Vec2::from(v.position)) + offset).into()
+}
+
+/// The offset of `v` rotated by `t`, as added by [`resolve_position`].
+fn resolve_offset(v: &Vertex, t: &Mat3, projection_scale: Vec2) -> Vec2 {
+    let unproject = Mat2::from_diagonal(projection_scale.abs().recip());
+    let linear = unproject * Mat2::from_cols(t.x_axis.truncate(), t.y_axis.truncate());
+    let scale = linear.determinant().abs().sqrt();
+    let offset = Vec2::from(v.offset);
+    if scale > 0.0 { linear * offset / scale } else { offset }
+}
+
+/// Result of tessellating one stroke.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrokeOutcome {
+    /// Triangles were appended to the geometry.
+    Tessellated,
+    /// Fewer than two points or non-finite coordinates; nothing was appended.
+    Skipped,
+    /// lyon reported an error; nothing was appended.
+    Failed,
+}
+
+/// Reusable lyon tessellator and buffers for strokes.
+///
+/// Tessellating through one `StrokeScratch` allocates nothing once its buffers
+/// have grown to fit the largest stroke, which keeps the per-frame path free
+/// of heap allocations.
+pub struct StrokeScratch {
+    tessellator: StrokeTessellator,
+    buffers: VertexBuffers<Vertex, u32>,
+    /// Point buffer for callers assembling paths, such as glyph outlines.
+    pub points: Vec<[f32; 2]>,
+}
+
+impl StrokeScratch {
+    /// Create a scratch with empty buffers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { tessellator: StrokeTessellator::new(), buffers: VertexBuffers::new(), points: Vec::new() }
+    }
+
+    /// Like [`tessellate_line`], reusing this scratch's buffers.
+    pub fn line(
+        &mut self,
+        line: &Line2,
+        transform: Option<&Mat3>,
+        px_to_ndc: f32,
+        geom: &mut Geometry,
+    ) -> StrokeOutcome {
+        let index = geom.push_transform(transform);
+        self.stroke(&[line.a, line.b], false, &line.stroke, px_to_ndc, index, geom)
+    }
+
+    /// Like [`tessellate_polyline`], reusing this scratch's buffers.
+    pub fn polyline(
+        &mut self,
+        pts: &[[f32; 2]],
+        closed: bool,
+        stroke: &Stroke,
+        transform: Option<&Mat3>,
+        px_to_ndc: f32,
+        geom: &mut Geometry,
+    ) -> StrokeOutcome {
+        let index = geom.push_transform(transform);
+        self.stroke(pts, closed, stroke, px_to_ndc, index, geom)
+    }
+
+    /// Tessellate a stroke whose vertices use transform `index` of `geom`.
+    pub(crate) fn stroke<P: Copy + Into<[f32; 2]>>(
+        &mut self,
+        pts: &[P],
+        closed: bool,
+        stroke: &Stroke,
+        px_to_ndc: f32,
+        index: u32,
+        geom: &mut Geometry,
+    ) -> StrokeOutcome {
+        if !is_drawable(pts) {
+            return StrokeOutcome::Skipped;
+        }
+
+        // Convert pixel width to NDC units
+        let width_ndc = stroke.width_px * px_to_ndc;
+        let options = StrokeOptions::default()
+            .with_line_width(width_ndc)
+            .with_line_cap(LineCap::Round)
+            .with_line_join(LineJoin::Round);
+        let c = stroke.color;
+        let (color, glow) = ([c.0, c.1, c.2, c.3], stroke.glow);
+
+        self.buffers.vertices.clear();
+        self.buffers.indices.clear();
+        let mut output = BuffersBuilder::new(&mut self.buffers, |v: StrokeVertex| {
+            let (center, edge) = (v.position_on_path(), v.position());
+            Vertex {
+                position: [center.x, center.y],
+                offset: [edge.x - center.x, edge.y - center.y],
+                color,
+                glow,
+                transform: index,
+            }
+        });
+        // Feed the points straight to the tessellator instead of building a `Path`.
+        let mut builder = self.tessellator.builder(&options, &mut output);
+        builder.begin(point(pts[0]));
+        for &p in &pts[1..] {
+            builder.line_to(point(p));
+        }
+        builder.end(closed);
+        if builder.build().is_err() {
+            return StrokeOutcome::Failed;
+        }
+        let base_vertex = geom.vertices.len() as u32;
+        geom.vertices.extend_from_slice(&self.buffers.vertices);
+        geom.indices.extend(self.buffers.indices.iter().map(|i| i + base_vertex));
+        StrokeOutcome::Tessellated
+    }
+}
+
+impl Default for StrokeScratch {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 /// Tessellate a single line segment into triangles.
 ///
+/// `transform` is recorded in `geom.transforms` rather than applied to the points.
 /// `px_to_ndc` converts pixel measurements to NDC units (typically `2.0 / viewport_height`).
+/// Allocates a fresh tessellator; per-frame code should use [`StrokeScratch::line`].
 pub fn tessellate_line(line: &Line2, transform: Option<&Mat3>, px_to_ndc: f32, geom: &mut Geometry) {
-    let xform = |p: [f32; 2], t: &Mat3| {
-        let r = *t * glam::Vec3::new(p[0], p[1], 1.0);
-        [r.x, r.y]
-    };
-    let (a, b): ([f32; 2], [f32; 2]) = match transform {
-        Some(t) => (xform(line.a.into(), t), xform(line.b.into(), t)),
-        None => (line.a.into(), line.b.into()),
-    };
-    tessellate_stroke(&[a, b], false, &line.stroke, px_to_ndc, geom);
+    StrokeScratch::new().line(line, transform, px_to_ndc, geom);
 }
 
 /// Tessellate a polyline into triangles.
 ///
+/// `transform` is recorded in `geom.transforms` rather than applied to the points.
 /// `px_to_ndc` converts pixel measurements to NDC units (typically `2.0 / viewport_height`).
+/// Allocates a fresh tessellator; per-frame code should use [`StrokeScratch::polyline`].
 pub fn tessellate_polyline(
     pts: &[[f32; 2]],
     closed: bool,
@@ -65,69 +314,15 @@ pub fn tessellate_polyline(
     px_to_ndc: f32,
     geom: &mut Geometry,
 ) {
-    let pts: Vec<[f32; 2]> = match transform {
-        Some(t) => pts
-            .iter()
-            .map(|p| {
-                let r = *t * glam::Vec3::new(p[0], p[1], 1.0);
-                [r.x, r.y]
-            })
-            .collect(),
-        None => pts.to_vec(),
-    };
-    tessellate_stroke(&pts, closed, stroke, px_to_ndc, geom);
-}
-
-fn tessellate_stroke(pts: &[[f32; 2]], closed: bool, stroke: &Stroke, px_to_ndc: f32, geom: &mut Geometry) {
-    if pts.len() < 2 {
-        return;
-    }
+    StrokeScratch::new().polyline(pts, closed, stroke, transform, px_to_ndc, geom);
+}
 
-    let path = build_path(pts, closed);
-    // Convert pixel width to NDC units
-    let width_ndc = stroke.width_px * px_to_ndc;
-    let options = StrokeOptions::default()
-        .with_line_width(width_ndc)
-        .with_line_cap(LineCap::Round)
-        .with_line_join(LineJoin::Round);
-    let color = [
-        stroke.color.0,
-        stroke.color.1,
-        stroke.color.2,
-        stroke.color.3,
-    ];
-
-    let base_vertex = geom.vertices.len() as u32;
-    let mut buffers: VertexBuffers<Vertex, u32> = VertexBuffers::new();
-    let mut tessellator = StrokeTessellator::new();
-
-    if tessellator
-        .tessellate_path(
-            &path,
-            &options,
-            &mut BuffersBuilder::new(&mut buffers, |v: lyon::tessellation::StrokeVertex| Vertex {
-                position: [v.position().x, v.position().y],
-                color,
-            }),
-        )
-        .is_ok()
-    {
-        geom.vertices.extend(buffers.vertices);
-        geom.indices
-            .extend(buffers.indices.iter().map(|i| i + base_vertex));
-    }
+/// Whether a stroke through `pts` can be tessellated: at least two points, all finite.
+pub(crate) fn is_drawable<P: Copy + Into<[f32; 2]>>(pts: &[P]) -> bool {
+    pts.len() >= 2 && pts.iter().all(|&p| p.into().iter().all(|c| c.is_finite()))
 }
 
-fn build_path(pts: &[[f32; 2]], closed: bool) -> Path {
-    let mut builder = Path::builder();
-    builder.begin(Point::new(pts[0][0], pts[0][1]));
-    for p in &pts[1..] {
-        builder.line_to(Point::new(p[0], p[1]));
-    }
-    if closed {
-        builder.close();
-    } else {
-        builder.end(false);
-    }
-    builder.build()
+fn point(p: impl Into<[f32; 2]>) -> Point {
+    let [x, y] = p.into();
+    Point::new(x, y)
 }
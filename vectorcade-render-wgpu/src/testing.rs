@@ -0,0 +1,215 @@
+//! Golden-image regression testing.
+//!
+//! Renders display lists with the deterministic [`SoftwareRenderer`] and
+//! compares the result against reference PNGs checked into the repository.
+//! Pixels are compared with a perceptual color distance so tiny coverage
+//! differences pass while wrong colors, transforms or glyphs fail.
+//!
+//! Set `VECTORCADE_BLESS=1` to (re)write references instead of comparing.
+//! A missing reference fails the check, so record new tests in bless mode
+//! and commit the new PNGs.
+//!
+//! ```ignore
+//! let golden = Golden::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"));
+//! golden.assert_matches("pong", &render_software(&cmds, 320, 240));
+//! ```
+
+use crate::image::RgbaImage;
+use crate::{SoftwareRenderer, VectorRenderer};
+use std::path::{Path, PathBuf};
+use vectorcade_shared::draw::DrawCmd;
+
+/// Environment variable enabling bless mode.
+pub const BLESS_ENV: &str = "VECTORCADE_BLESS";
+
+/// Largest squared YIQ distance between two colors (black vs. white).
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+/// How different an image may be from its reference.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// Perceptual distance (0-1) above which a pixel counts as different.
+    pub threshold: f32,
+    /// Fraction of pixels (0-1) allowed to differ.
+    pub max_diff_ratio: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self { threshold: 0.1, max_diff_ratio: 0.001 }
+    }
+}
+
+/// Result of comparing two equally sized images.
+#[derive(Clone, Debug)]
+pub struct ImageDiff {
+    /// Number of pixels over the threshold.
+    pub differing: usize,
+    /// Total number of pixels compared.
+    pub total: usize,
+    /// Largest perceptual distance found (0-1).
+    pub max_delta: f32,
+    /// Visualization: differing pixels in red over a faded copy of the reference.
+    pub image: RgbaImage,
+}
+
+impl ImageDiff {
+    /// Fraction of pixels that differ.
+    #[must_use]
+    pub fn ratio(&self) -> f32 {
+        self.differing as f32 / self.total.max(1) as f32
+    }
+}
+
+/// Render `cmds` at `width` x `height` with the software renderer.
+#[must_use]
+pub fn render_software(cmds: &[DrawCmd], width: u32, height: u32) -> RgbaImage {
+    let mut renderer = SoftwareRenderer::new(width, height);
+    renderer.render(cmds);
+    renderer.into_image()
+}
+
+/// Compare `actual` against `expected`, or `None` if their sizes differ.
+#[must_use]
+pub fn diff_images(actual: &RgbaImage, expected: &RgbaImage, threshold: f32) -> Option<ImageDiff> {
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        return None;
+    }
+    let mut image = RgbaImage::new(expected.width, expected.height);
+    let (mut differing, mut max_delta) = (0, 0.0f32);
+    let pixels = actual.pixels.chunks_exact(4).zip(expected.pixels.chunks_exact(4));
+    for ((a, e), out) in pixels.zip(image.pixels.chunks_exact_mut(4)) {
+        let delta = color_delta(a, e);
+        max_delta = max_delta.max(delta);
+        if delta > threshold {
+            differing += 1;
+            out.copy_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let y = (yiq(blend_white(e))[0] * 0.1 + 255.0 * 0.9) as u8;
+            out.copy_from_slice(&[y, y, y, 255]);
+        }
+    }
+    let total = (expected.width * expected.height) as usize;
+    Some(ImageDiff { differing, total, max_delta, image })
+}
+
+/// Golden-image store rooted at a directory of reference PNGs.
+#[derive(Clone, Debug)]
+pub struct Golden {
+    dir: PathBuf,
+    tolerance: Tolerance,
+    bless: bool,
+}
+
+impl Golden {
+    /// Use references in `dir`; bless mode follows [`BLESS_ENV`].
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let bless = std::env::var(BLESS_ENV).is_ok_and(|v| !v.is_empty() && v != "0");
+        Self { dir: dir.into(), tolerance: Tolerance::default(), bless }
+    }
+
+    /// Override the comparison tolerance.
+    #[must_use]
+    pub fn with_tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Override bless mode.
+    #[must_use]
+    pub fn with_bless(mut self, bless: bool) -> Self {
+        self.bless = bless;
+        self
+    }
+
+    /// Path of the reference image for `name`.
+    #[must_use]
+    pub fn reference_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.png"))
+    }
+
+    /// Compare `actual` against the reference `name`.
+    ///
+    /// On failure `<name>.actual.png` and `<name>.diff.png` are written next to
+    /// the reference. In bless mode the reference is written instead.
+    ///
+    /// # Errors
+    /// Returns a description of the mismatch, of a missing reference, or of
+    /// an I/O failure.
+    pub fn check(&self, name: &str, actual: &RgbaImage) -> Result<(), String> {
+        let reference = self.reference_path(name);
+        if self.bless {
+            return write(actual, &reference);
+        }
+        if !reference.exists() {
+            let actual_path = self.dir.join(format!("{name}.actual.png"));
+            write(actual, &actual_path)?;
+            return Err(format!(
+                "{name}: no reference at {}; re-run with {BLESS_ENV}=1 to record it from {}",
+                reference.display(),
+                actual_path.display()
+            ));
+        }
+        let expected = RgbaImage::load_png(&reference).map_err(|e| format!("{}: {e}", reference.display()))?;
+        let actual_path = self.dir.join(format!("{name}.actual.png"));
+        let diff_path = self.dir.join(format!("{name}.diff.png"));
+
+        let Some(diff) = diff_images(actual, &expected, self.tolerance.threshold) else {
+            write(actual, &actual_path)?;
+            return Err(format!(
+                "{name}: size {}x{} does not match reference {}x{}; see {}",
+                actual.width, actual.height, expected.width, expected.height, actual_path.display()
+            ));
+        };
+        if diff.ratio() <= self.tolerance.max_diff_ratio {
+            let _ = std::fs::remove_file(&actual_path);
+            let _ = std::fs::remove_file(&diff_path);
+            return Ok(());
+        }
+        write(actual, &actual_path)?;
+        write(&diff.image, &diff_path)?;
+        Err(format!(
+            "{name}: {} of {} pixels differ (max delta {:.3}); see {}. Set {BLESS_ENV}=1 to accept.",
+            diff.differing, diff.total, diff.max_delta, diff_path.display()
+        ))
+    }
+
+    /// Like [`check`](Self::check), but panics on mismatch.
+    ///
+    /// # Panics
+    /// Panics if the image does not match its reference.
+    pub fn assert_matches(&self, name: &str, actual: &RgbaImage) {
+        if let Err(e) = self.check(name, actual) {
+            panic!("golden image mismatch: {e}");
+        }
+    }
+}
+
+fn write(image: &RgbaImage, path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    }
+    image.save_png(path).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Perceptual distance (0-1) between two RGBA8 pixels, as used by pixelmatch.
+fn color_delta(a: &[u8], b: &[u8]) -> f32 {
+    let (a, b) = (yiq(blend_white(a)), yiq(blend_white(b)));
+    let (y, i, q) = (a[0] - b[0], a[1] - b[1], a[2] - b[2]);
+    ((0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q) / MAX_YIQ_DELTA).sqrt()
+}
+
+/// Composite a pixel over white so transparency differences are visible.
+fn blend_white(p: &[u8]) -> [f32; 3] {
+    let a = f32::from(p[3]) / 255.0;
+    [0, 1, 2].map(|c| 255.0 + (f32::from(p[c]) - 255.0) * a)
+}
+
+fn yiq([r, g, b]: [f32; 3]) -> [f32; 3] {
+    [
+        r * 0.298_895 + g * 0.586_623 + b * 0.114_482,
+        r * 0.595_978 - g * 0.274_176 - b * 0.321_802,
+        r * 0.211_47 - g * 0.522_617 + b * 0.311_147,
+    ]
+}
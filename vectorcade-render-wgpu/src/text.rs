@@ -0,0 +1,115 @@
+//! Text rendering via vector fonts.
+
+use crate::tessellate::{Geometry, StrokeOutcome, StrokeScratch};
+use glam::{Mat3, Vec2};
+use vectorcade_fonts::FontRegistry;
+use vectorcade_shared::draw::Stroke;
+use vectorcade_shared::font::{FontStyleId, GlyphPathCmd};
+use vectorcade_shared::Rgba;
+
+/// Parameters for text rendering.
+pub struct TextParams<'a> {
+    pub registry: &'a FontRegistry,
+    pub text: &'a str,
+    pub pos: Vec2,
+    pub size_px: f32,
+    pub color: Rgba,
+    pub style: FontStyleId,
+    /// Pixel to NDC conversion factor (typically `2.0 / viewport_height`).
+    pub px_to_ndc: f32,
+    /// Signed x and y scale of the world-to-NDC projection. Glyphs are
+    /// divided by it so they are `size_px` tall and upright in any world space.
+    pub projection_scale: Vec2,
+}
+
+/// Render text into geometry using the given font registry, reusing `scratch`'s buffers.
+///
+/// Returns the number of glyph paths lyon failed to tessellate.
+pub fn tessellate_text(
+    params: &TextParams<'_>,
+    transform: Option<&Mat3>,
+    scratch: &mut StrokeScratch,
+    geom: &mut Geometry,
+) -> u32 {
+    let stroke = text_stroke(params);
+    let mut points = std::mem::take(&mut scratch.points);
+    let mut failed = 0;
+    for_each_glyph_path(params, &mut points, |pts, closed| {
+        if scratch.polyline(pts, closed, &stroke, transform, params.px_to_ndc, geom) == StrokeOutcome::Failed {
+            failed += 1;
+        }
+    });
+    scratch.points = points;
+    failed
+}
+
+/// Stroke used for glyph outlines; width is in pixels.
+pub fn text_stroke(params: &TextParams<'_>) -> Stroke {
+    Stroke::new(params.color, params.size_px * 0.08)
+}
+
+/// Call `emit` with the points and closed flag of every glyph path in the text.
+///
+/// Points are untransformed, in the same space as `params.pos`. Paths with
+/// fewer than two points are skipped. `pts` is used as the buffer for each path.
+pub fn for_each_glyph_path(
+    params: &TextParams<'_>,
+    pts: &mut Vec<[f32; 2]>,
+    mut emit: impl FnMut(&[[f32; 2]], bool),
+) {
+    let Some(font) = params.registry.get(params.style) else { return };
+    // Convert size from pixels to world units, using the vertical scale on both
+    // axes and flipping each axis the projection flips.
+    let size_ndc = params.size_px * params.px_to_ndc;
+    let [sx, sy] = params.projection_scale.to_array();
+    let scale = Vec2::new(size_ndc / sy.abs() * sx.signum(), size_ndc / sy);
+    let mut cursor_x = params.pos.x;
+
+    for ch in params.text.chars() {
+        if font.has_glyph(ch) {
+            for path in font.glyph_paths(ch) {
+                walk_glyph(&path.cmds, [cursor_x, params.pos.y], scale, pts, &mut emit);
+            }
+        }
+        cursor_x += font.advance(ch) * scale.x;
+    }
+}
+
+fn walk_glyph(
+    cmds: &[GlyphPathCmd],
+    offset: [f32; 2],
+    scale: Vec2,
+    pts: &mut Vec<[f32; 2]>,
+    emit: &mut impl FnMut(&[[f32; 2]], bool),
+) {
+    pts.clear();
+    let mut closed = false;
+
+    for cmd in cmds {
+        match cmd {
+            GlyphPathCmd::MoveTo(p) => {
+                flush_path(pts, closed, emit);
+                pts.clear();
+                closed = false;
+                pts.push(transform_glyph_pt(*p, offset, scale));
+            }
+            GlyphPathCmd::LineTo(p) => {
+                pts.push(transform_glyph_pt(*p, offset, scale));
+            }
+            GlyphPathCmd::Close => {
+                closed = true;
+            }
+        }
+    }
+    flush_path(pts, closed, emit);
+}
+
+fn transform_glyph_pt(p: Vec2, offset: [f32; 2], scale: Vec2) -> [f32; 2] {
+    [offset[0] + p.x * scale.x, offset[1] + p.y * scale.y]
+}
+
+fn flush_path(pts: &[[f32; 2]], closed: bool, emit: &mut impl FnMut(&[[f32; 2]], bool)) {
+    if pts.len() >= 2 {
+        emit(pts, closed);
+    }
+}
@@ -1,52 +1,83 @@
 //! GPU buffer management with pooling and dynamic resizing.
 
-use crate::tessellate::Vertex;
+use crate::RenderStats;
+use crate::tessellate::{LineInstance, Vertex};
 
-/// Manages vertex and index buffers with capacity tracking.
+/// Manages vertex, index and line instance buffers with capacity tracking.
 pub struct BufferPool {
     pub vertex: wgpu::Buffer,
     pub index: wgpu::Buffer,
+    pub instance: wgpu::Buffer,
     vertex_capacity: usize,
     index_capacity: usize,
+    instance_capacity: usize,
 }
 
 impl BufferPool {
     /// Initial buffer capacity (number of vertices/indices).
     const INITIAL_VERTICES: usize = 4096;
     const INITIAL_INDICES: usize = 8192;
+    const INITIAL_INSTANCES: usize = 1024;
 
     /// Create a new buffer pool with default capacity.
     pub fn new(device: &wgpu::Device) -> Self {
         Self {
             vertex: create_vertex_buffer(device, Self::INITIAL_VERTICES),
             index: create_index_buffer(device, Self::INITIAL_INDICES),
+            instance: create_instance_buffer(device, Self::INITIAL_INSTANCES),
             vertex_capacity: Self::INITIAL_VERTICES,
             index_capacity: Self::INITIAL_INDICES,
+            instance_capacity: Self::INITIAL_INSTANCES,
         }
     }
 
     /// Update buffers with new geometry, resizing if needed.
+    ///
+    /// Uploaded bytes and reallocations are added to `stats`.
     pub fn update(
         &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         vertices: &[Vertex],
         indices: &[u32],
+        stats: &mut RenderStats,
     ) {
         if vertices.len() > self.vertex_capacity {
             self.vertex_capacity = next_capacity(vertices.len());
             self.vertex = create_vertex_buffer(device, self.vertex_capacity);
+            stats.buffer_reallocations += 1;
         }
         if indices.len() > self.index_capacity {
             self.index_capacity = next_capacity(indices.len());
             self.index = create_index_buffer(device, self.index_capacity);
+            stats.buffer_reallocations += 1;
         }
-        if !vertices.is_empty() {
-            queue.write_buffer(&self.vertex, 0, bytemuck::cast_slice(vertices));
-        }
-        if !indices.is_empty() {
-            queue.write_buffer(&self.index, 0, bytemuck::cast_slice(indices));
+        write(queue, &self.vertex, bytemuck::cast_slice(vertices), stats);
+        write(queue, &self.index, bytemuck::cast_slice(indices), stats);
+    }
+
+    /// Update the line instance buffer, resizing if needed.
+    pub fn update_instances(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        instances: &[LineInstance],
+        stats: &mut RenderStats,
+    ) {
+        if instances.len() > self.instance_capacity {
+            self.instance_capacity = next_capacity(instances.len());
+            self.instance = create_instance_buffer(device, self.instance_capacity);
+            stats.buffer_reallocations += 1;
         }
+        write(queue, &self.instance, bytemuck::cast_slice(instances), stats);
+    }
+}
+
+/// Write `bytes` to the start of `buffer`, counting them in `stats`.
+pub fn write(queue: &wgpu::Queue, buffer: &wgpu::Buffer, bytes: &[u8], stats: &mut RenderStats) {
+    if !bytes.is_empty() {
+        queue.write_buffer(buffer, 0, bytes);
+        stats.bytes_uploaded += bytes.len() as u64;
     }
 }
 
@@ -68,6 +99,15 @@ fn create_index_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
     })
 }
 
+fn create_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Line Instance Buffer"),
+        size: (capacity * std::mem::size_of::<LineInstance>()) as u64,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
 fn next_capacity(required: usize) -> usize {
     required.next_power_of_two()
 }
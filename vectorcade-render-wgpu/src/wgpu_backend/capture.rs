@@ -0,0 +1,124 @@
+//! Framebuffer readback into [`RgbaImage`].
+//!
+//! Native only: readback blocks on `Device::poll`, which cannot wait in browsers.
+
+use super::{RenderError, WgpuRenderer};
+use crate::image::RgbaImage;
+
+impl WgpuRenderer {
+    /// Capture the last rendered frame as an RGBA8 image.
+    ///
+    /// Works for both windowed and headless renderers: the last frame's
+    /// resolved HDR targets are composited again into a copyable texture, so
+    /// MSAA is already resolved and the sRGB encoding matches the target.
+    /// Blocks until the GPU has finished.
+    ///
+    /// # Errors
+    /// Returns [`RenderError::Readback`] if the readback buffer cannot be mapped.
+    pub fn capture_frame(&mut self) -> Result<RgbaImage, RenderError> {
+        let (width, height) = self.target.size();
+        let format = capture_format(self.target.format());
+        let texture = create_capture_texture(&self.device, format, width, height);
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        self.composite.encode_again(&self.device, &mut encoder, &view, format);
+        self.queue.submit(std::iter::once(encoder.finish()));
+        read_texture(&self.device, &self.queue, &texture)
+    }
+}
+
+/// The RGBA8 format a capture of `target` is rendered into.
+///
+/// Keeps the target's sRGB-ness so captured bytes match what the target shows:
+/// sRGB targets are encoded by the hardware, linear targets are stored as-is.
+pub fn capture_format(target: wgpu::TextureFormat) -> wgpu::TextureFormat {
+    if target.is_srgb() {
+        wgpu::TextureFormat::Rgba8UnormSrgb
+    } else {
+        wgpu::TextureFormat::Rgba8Unorm
+    }
+}
+
+/// Create a single-sample texture the composite pass can be re-run into.
+pub fn create_capture_texture(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Capture Texture"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// Copy an 8-bit RGBA or BGRA texture to the CPU, blocking until it is mapped.
+///
+/// Rows are copied with `COPY_BYTES_PER_ROW_ALIGNMENT` padding and repacked
+/// tightly; BGRA data is swizzled to RGBA.
+///
+/// # Errors
+/// Returns [`RenderError::UnreadableFormat`] if the format is not 8-bit
+/// RGBA/BGRA, or [`RenderError::Readback`] if mapping fails.
+pub fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Result<RgbaImage, RenderError> {
+    let format = texture.format();
+    let bgra = match format.remove_srgb_suffix() {
+        wgpu::TextureFormat::Rgba8Unorm => false,
+        wgpu::TextureFormat::Bgra8Unorm => true,
+        _ => return Err(RenderError::UnreadableFormat(format)),
+    };
+    let (width, height) = (texture.width(), texture.height());
+    let row_bytes = width * 4;
+    let padded_row_bytes = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Capture Readback"),
+        size: u64::from(padded_row_bytes * height),
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&Default::default());
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_bytes),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    // A dropped callback means the buffer was never mapped.
+    rx.recv().unwrap_or(Err(wgpu::BufferAsyncError))?;
+
+    let mut pixels = Vec::with_capacity((row_bytes * height) as usize);
+    for row in slice.get_mapped_range().chunks_exact(padded_row_bytes as usize) {
+        pixels.extend_from_slice(&row[..row_bytes as usize]);
+    }
+    buffer.unmap();
+    if bgra {
+        pixels.chunks_exact_mut(4).for_each(|px| px.swap(0, 2));
+    }
+    Ok(RgbaImage { width, height, pixels })
+}
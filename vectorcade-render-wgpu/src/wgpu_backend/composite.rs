@@ -0,0 +1,222 @@
+//! Final composite: scene over the clear color, plus scaled bloom, placed in
+//! the viewport rect with bars around it.
+
+use super::fullscreen::{
+    FULLSCREEN_VS, create_linear_sampler, fullscreen_pipeline, sampler_entry, texture_entry, uniform_entry,
+};
+
+/// Blend the premultiplied scene over the background and add the glow.
+const COMPOSITE_FS: &str = r#"
+struct CompositeParams {
+    background: vec4<f32>,
+    glow_intensity: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+    rect: vec4<f32>,
+    bars: vec4<f32>,
+};
+
+@group(0) @binding(0) var scene: texture_2d<f32>;
+@group(0) @binding(1) var bloom: texture_2d<f32>;
+@group(0) @binding(2) var linear_sampler: sampler;
+@group(0) @binding(3) var<uniform> params: CompositeParams;
+
+@fragment
+fn fs_composite(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let uv = (in.uv - params.rect.xy) / params.rect.zw;
+    let color = textureSample(scene, linear_sampler, uv);
+    let glow = textureSample(bloom, linear_sampler, uv).rgb * params.glow_intensity;
+    let bg = params.background;
+    let rgb = bg.rgb * (1.0 - color.a) + color.rgb + glow;
+    let a = bg.a * (1.0 - color.a) + color.a;
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+    return select(params.bars, vec4<f32>(rgb, a), inside);
+}
+"#;
+
+/// What [`WgpuRenderer::render_to`](super::WgpuRenderer::render_to) does with the view's existing contents.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoadMode {
+    /// Replace them with the display list's `Clear` color and the frame.
+    #[default]
+    Clear,
+    /// Keep them and blend the frame over them; `Clear` colors are ignored.
+    Load,
+}
+
+/// Per-frame inputs of the composite pass.
+#[derive(Clone, Copy, Debug)]
+pub struct CompositeFrame {
+    /// Straight-alpha clear color behind the scene.
+    pub background: [f32; 4],
+    pub glow_intensity: f32,
+    /// Viewport rect in target UV coordinates: origin, then size.
+    pub rect: [f32; 4],
+    /// Straight-alpha color outside `rect`.
+    pub bars: [f32; 4],
+}
+
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct CompositeParams {
+    background: [f32; 4],
+    glow_intensity: f32,
+    _pad: [f32; 3],
+    rect: [f32; 4],
+    bars: [f32; 4],
+}
+
+/// Pipeline and bindings for the composite pass.
+pub struct Composite {
+    pipeline: wgpu::RenderPipeline,
+    /// `pipeline` with premultiplied alpha blending, for `LoadMode::Load`.
+    blend_pipeline: wgpu::RenderPipeline,
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    format: wgpu::TextureFormat,
+    /// Pipeline for a second output format, created on first capture.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    capture: Option<(wgpu::TextureFormat, wgpu::RenderPipeline)>,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    params: wgpu::Buffer,
+    group: wgpu::BindGroup,
+}
+
+impl Composite {
+    /// Create the composite pass writing to `format`.
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        scene: &wgpu::TextureView,
+        bloom: &wgpu::TextureView,
+    ) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Composite Layout"),
+            entries: &[texture_entry(0), texture_entry(1), sampler_entry(2), uniform_entry(3)],
+        });
+        let pipeline = create_pipeline(device, &layout, format, None);
+        let premultiplied = Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING);
+        let blend_pipeline = create_pipeline(device, &layout, format, premultiplied);
+        let sampler = create_linear_sampler(device);
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Composite Params"),
+            size: std::mem::size_of::<CompositeParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let group = create_group(device, &layout, &sampler, &params, scene, bloom);
+        Self { pipeline, blend_pipeline, format, capture: None, layout, sampler, params, group }
+    }
+
+    /// Rebind inputs after the scene or bloom targets were recreated.
+    pub fn rebind(&mut self, device: &wgpu::Device, scene: &wgpu::TextureView, bloom: &wgpu::TextureView) {
+        self.group = create_group(device, &self.layout, &self.sampler, &self.params, scene, bloom);
+    }
+
+    /// Record the composite pass into `target`, writing `timestamps` if profiling.
+    ///
+    /// With `LoadMode::Load` the background is dropped and the premultiplied
+    /// result, bars included, is blended over the target's contents.
+    pub fn encode(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        frame: CompositeFrame,
+        load: LoadMode,
+        timestamps: Option<wgpu::RenderPassTimestampWrites<'_>>,
+    ) {
+        let CompositeFrame { background, glow_intensity, rect, bars } = frame;
+        let (background, bars, pipeline, load) = match load {
+            LoadMode::Clear => (background, bars, &self.pipeline, wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)),
+            LoadMode::Load => {
+                let [r, g, b, a] = bars;
+                ([0.0; 4], [r * a, g * a, b * a, a], &self.blend_pipeline, wgpu::LoadOp::Load)
+            }
+        };
+        let params = CompositeParams { background, glow_intensity, _pad: [0.0; 3], rect, bars };
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        self.draw(encoder, target, pipeline, load, timestamps);
+    }
+
+    /// Record the last composite again into a `target` of any color `format`.
+    ///
+    /// Reuses the parameters of the previous [`Composite::encode`], so the
+    /// result matches what was last shown.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn encode_again(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        format: wgpu::TextureFormat,
+    ) {
+        let pipeline = if format == self.format {
+            self.pipeline.clone()
+        } else {
+            match &self.capture {
+                Some((f, pipeline)) if *f == format => pipeline.clone(),
+                _ => {
+                    let pipeline = create_pipeline(device, &self.layout, format, None);
+                    self.capture = Some((format, pipeline.clone()));
+                    pipeline
+                }
+            }
+        };
+        self.draw(encoder, target, &pipeline, wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), None);
+    }
+
+    fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        pipeline: &wgpu::RenderPipeline,
+        load: wgpu::LoadOp<wgpu::Color>,
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites<'_>>,
+    ) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations { load, store: wgpu::StoreOp::Store },
+            })],
+            timestamp_writes,
+            ..Default::default()
+        });
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &self.group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    format: wgpu::TextureFormat,
+    blend: Option<wgpu::BlendState>,
+) -> wgpu::RenderPipeline {
+    let source = format!("{FULLSCREEN_VS}{COMPOSITE_FS}");
+    fullscreen_pipeline(device, "Composite", &source, "fs_composite", layout, &[format], blend)
+}
+
+fn create_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    params: &wgpu::Buffer,
+    scene: &wgpu::TextureView,
+    bloom: &wgpu::TextureView,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Composite Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(scene) },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(bloom) },
+            wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(sampler) },
+            wgpu::BindGroupEntry { binding: 3, resource: params.as_entire_binding() },
+        ],
+    })
+}
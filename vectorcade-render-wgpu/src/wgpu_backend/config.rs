@@ -0,0 +1,120 @@
+//! Renderer configuration: presentation, adapter selection and MSAA.
+
+use super::WgpuRenderer;
+use super::pipeline::DEFAULT_SAMPLE_COUNT;
+
+/// Options for creating a [`WgpuRenderer`].
+///
+/// Presentation options that the surface does not support fall back to a
+/// supported mode, so any value is safe to request. `present_mode`,
+/// `frame_latency` and `alpha_mode` can also be changed after creation;
+/// `power_preference` and `backends` select the adapter and only apply at
+/// creation. Headless renderers ignore the presentation options.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RendererConfig {
+    /// How frames are queued for display: `Fifo` (vsync, the default),
+    /// `Mailbox` (low-latency vsync) or `Immediate` (uncapped, may tear).
+    pub present_mode: wgpu::PresentMode,
+    /// Frames the GPU may queue ahead of the display; lower reduces input latency.
+    pub frame_latency: u32,
+    /// Prefer the integrated (`LowPower`) or discrete (`HighPerformance`) GPU.
+    pub power_preference: wgpu::PowerPreference,
+    /// Graphics APIs the adapter may use.
+    pub backends: wgpu::Backends,
+    /// How the window compositor blends the surface with what is behind it.
+    pub alpha_mode: wgpu::CompositeAlphaMode,
+    /// Requested MSAA sample count; see [`WgpuRenderer::set_sample_count`].
+    pub sample_count: u32,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            present_mode: wgpu::PresentMode::Fifo,
+            frame_latency: 2,
+            power_preference: wgpu::PowerPreference::default(),
+            backends: wgpu::Backends::all(),
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            sample_count: DEFAULT_SAMPLE_COUNT,
+        }
+    }
+}
+
+/// Present and alpha modes a surface supports on the chosen adapter.
+pub struct SurfaceModes {
+    present: Vec<wgpu::PresentMode>,
+    alpha: Vec<wgpu::CompositeAlphaMode>,
+}
+
+impl SurfaceModes {
+    pub fn new(caps: &wgpu::SurfaceCapabilities) -> Self {
+        Self { present: caps.present_modes.clone(), alpha: caps.alpha_modes.clone() }
+    }
+
+    /// `requested` if supported, else the closest supported mode; `Fifo` is always available.
+    ///
+    /// The `Auto` modes are resolved by wgpu itself and kept as they are.
+    pub fn present_mode(&self, requested: wgpu::PresentMode) -> wgpu::PresentMode {
+        use wgpu::PresentMode::{AutoNoVsync, AutoVsync, Fifo, FifoRelaxed, Immediate, Mailbox};
+        let candidates: &[wgpu::PresentMode] = match requested {
+            AutoVsync | AutoNoVsync => return requested,
+            Mailbox => &[Mailbox, Immediate],
+            Immediate => &[Immediate, Mailbox],
+            FifoRelaxed => &[FifoRelaxed],
+            Fifo => &[],
+        };
+        candidates.iter().copied().find(|mode| self.present.contains(mode)).unwrap_or(Fifo)
+    }
+
+    /// `requested` if supported, else the surface's preferred alpha mode.
+    pub fn alpha_mode(&self, requested: wgpu::CompositeAlphaMode) -> wgpu::CompositeAlphaMode {
+        if requested == wgpu::CompositeAlphaMode::Auto || self.alpha.contains(&requested) {
+            requested
+        } else {
+            self.alpha.first().copied().unwrap_or(wgpu::CompositeAlphaMode::Auto)
+        }
+    }
+}
+
+impl WgpuRenderer {
+    /// Current present mode, or `None` for headless renderers.
+    #[must_use]
+    pub fn present_mode(&self) -> Option<wgpu::PresentMode> {
+        self.target.surface_config().map(|config| config.present_mode)
+    }
+
+    /// Switch the present mode, falling back to the closest supported one.
+    ///
+    /// Returns the mode now in use, or `None` for headless renderers.
+    pub fn set_present_mode(&mut self, mode: wgpu::PresentMode) -> Option<wgpu::PresentMode> {
+        self.target.reconfigure(&self.device, |config, modes| config.present_mode = modes.present_mode(mode));
+        self.present_mode()
+    }
+
+    /// Current maximum frame latency, or `None` for headless renderers.
+    #[must_use]
+    pub fn frame_latency(&self) -> Option<u32> {
+        self.target.surface_config().map(|config| config.desired_maximum_frame_latency)
+    }
+
+    /// Set how many frames the GPU may queue ahead of the display.
+    ///
+    /// Backends clamp this to the range they support. Ignored by headless renderers.
+    pub fn set_frame_latency(&mut self, frames: u32) {
+        self.target.reconfigure(&self.device, |config, _| config.desired_maximum_frame_latency = frames);
+    }
+
+    /// Current surface alpha mode, or `None` for headless renderers.
+    #[must_use]
+    pub fn alpha_mode(&self) -> Option<wgpu::CompositeAlphaMode> {
+        self.target.surface_config().map(|config| config.alpha_mode)
+    }
+
+    /// Switch the surface alpha mode, falling back to the surface's preferred mode.
+    ///
+    /// Returns the mode now in use, or `None` for headless renderers.
+    pub fn set_alpha_mode(&mut self, mode: wgpu::CompositeAlphaMode) -> Option<wgpu::CompositeAlphaMode> {
+        self.target.reconfigure(&self.device, |config, modes| config.alpha_mode = modes.alpha_mode(mode));
+        self.alpha_mode()
+    }
+}
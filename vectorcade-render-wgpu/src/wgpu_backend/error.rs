@@ -0,0 +1,89 @@
+//! Errors from creating a renderer and from rendering frames.
+
+use std::fmt;
+
+/// Why a [`WgpuRenderer`](super::WgpuRenderer) could not be created, render a frame or read it back.
+#[derive(Debug)]
+pub enum RenderError {
+    /// No GPU adapter, nor the software fallback adapter, is available.
+    NoAdapter,
+    /// The adapter refused to create a device.
+    RequestDevice(wgpu::RequestDeviceError),
+    /// The window or canvas surface could not be created.
+    CreateSurface(wgpu::CreateSurfaceError),
+    /// The surface offers no texture format to render into.
+    UnsupportedFormat,
+    /// The surface was still lost after reconfiguring it.
+    SurfaceLost,
+    /// The surface still did not match the window after reconfiguring it.
+    SurfaceOutdated,
+    /// Acquiring the next surface texture timed out. `try_render` skips such frames instead.
+    SurfaceTimeout,
+    /// Acquiring the next surface texture failed for an unspecified reason.
+    SurfaceUnavailable,
+    /// The GPU ran out of memory.
+    OutOfMemory,
+    /// A texture of this format cannot be read back as RGBA8.
+    UnreadableFormat(wgpu::TextureFormat),
+    /// The readback buffer could not be mapped.
+    Readback(wgpu::BufferAsyncError),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAdapter => write!(f, "no GPU or fallback adapter found"),
+            Self::RequestDevice(e) => write!(f, "failed to create GPU device: {e}"),
+            Self::CreateSurface(e) => write!(f, "failed to create surface: {e}"),
+            Self::UnsupportedFormat => write!(f, "surface has no supported texture format"),
+            Self::SurfaceLost => write!(f, "surface lost"),
+            Self::SurfaceOutdated => write!(f, "surface outdated"),
+            Self::SurfaceTimeout => write!(f, "timed out acquiring the next surface texture"),
+            Self::SurfaceUnavailable => write!(f, "failed to acquire the next surface texture"),
+            Self::OutOfMemory => write!(f, "out of GPU memory"),
+            Self::UnreadableFormat(format) => write!(f, "cannot read back texture format {format:?}"),
+            Self::Readback(e) => write!(f, "failed to map the readback buffer: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::RequestDevice(e) => Some(e),
+            Self::CreateSurface(e) => Some(e),
+            Self::Readback(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for RenderError {
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        Self::RequestDevice(e)
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for RenderError {
+    fn from(e: wgpu::CreateSurfaceError) -> Self {
+        Self::CreateSurface(e)
+    }
+}
+
+impl From<wgpu::BufferAsyncError> for RenderError {
+    fn from(e: wgpu::BufferAsyncError) -> Self {
+        Self::Readback(e)
+    }
+}
+
+impl From<wgpu::SurfaceError> for RenderError {
+    fn from(e: wgpu::SurfaceError) -> Self {
+        match e {
+            wgpu::SurfaceError::Lost => Self::SurfaceLost,
+            wgpu::SurfaceError::Outdated => Self::SurfaceOutdated,
+            wgpu::SurfaceError::Timeout => Self::SurfaceTimeout,
+            wgpu::SurfaceError::OutOfMemory => Self::OutOfMemory,
+            wgpu::SurfaceError::Other => Self::SurfaceUnavailable,
+        }
+    }
+}
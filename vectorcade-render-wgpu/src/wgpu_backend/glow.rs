@@ -0,0 +1,191 @@
+//! Phosphor glow: separable Gaussian blur chain over the scene's glow target.
+//!
+//! The glow source is downsampled to half resolution by the first horizontal
+//! pass, then blurred back and forth between two half-resolution textures.
+
+use super::fullscreen::{
+    FULLSCREEN_VS, create_linear_sampler, fullscreen_pipeline, sampler_entry, texture_entry, uniform_entry,
+};
+use super::pipeline::HDR_FORMAT;
+use super::targets::{clear_attachment, create_view};
+use wgpu::util::DeviceExt;
+
+/// Number of horizontal + vertical blur iterations.
+const BLUR_ITERATIONS: usize = 3;
+
+/// 9-tap Gaussian blur using linear filtering (5 fetches per pass).
+const BLUR_FS: &str = r#"
+struct BlurParams {
+    texel_step: vec2<f32>,
+    _pad: vec2<f32>,
+};
+
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+@group(0) @binding(2) var<uniform> params: BlurParams;
+
+@fragment
+fn fs_blur(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    var offsets = array<f32, 3>(0.0, 1.3846153846, 3.2307692308);
+    var weights = array<f32, 3>(0.2270270270, 0.3162162162, 0.0702702703);
+    var sum = textureSample(src, src_sampler, in.uv).rgb * weights[0];
+    for (var i = 1; i < 3; i++) {
+        let o = params.texel_step * offsets[i];
+        sum += textureSample(src, src_sampler, in.uv + o).rgb * weights[i];
+        sum += textureSample(src, src_sampler, in.uv - o).rgb * weights[i];
+    }
+    return vec4<f32>(sum, 1.0);
+}
+"#;
+
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct BlurParams {
+    texel_step: [f32; 2],
+    _pad: [f32; 2],
+}
+
+/// Multi-pass bloom over the glow target.
+pub struct GlowPass {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    chain: BlurChain,
+}
+
+/// Half-resolution ping-pong textures and the bind groups that read them.
+struct BlurChain {
+    a: wgpu::TextureView,
+    b: wgpu::TextureView,
+    /// Glow source -> `a`, horizontal.
+    from_source: wgpu::BindGroup,
+    /// `a` -> `b`, vertical.
+    from_a: wgpu::BindGroup,
+    /// `b` -> `a`, horizontal.
+    from_b: wgpu::BindGroup,
+}
+
+impl GlowPass {
+    /// Draw calls recorded by [`GlowPass::encode`]: one per blur direction and iteration.
+    pub const DRAW_CALLS: u32 = 2 * BLUR_ITERATIONS as u32;
+
+    /// Create the blur pipeline and targets for the given glow source.
+    pub fn new(device: &wgpu::Device, source: &wgpu::TextureView, width: u32, height: u32) -> Self {
+        let layout = bind_group_layout(device);
+        let pipeline = create_pipeline(device, &layout);
+        let sampler = create_linear_sampler(device);
+        let chain = BlurChain::new(device, &layout, &sampler, source, width, height);
+        Self { pipeline, layout, sampler, chain }
+    }
+
+    /// Recreate the blur targets for a new glow source or size.
+    pub fn rebind(&mut self, device: &wgpu::Device, source: &wgpu::TextureView, width: u32, height: u32) {
+        self.chain = BlurChain::new(device, &self.layout, &self.sampler, source, width, height);
+    }
+
+    /// The fully blurred glow, valid after [`GlowPass::encode`].
+    pub fn output(&self) -> &wgpu::TextureView {
+        &self.chain.b
+    }
+
+    /// Record the blur passes.
+    ///
+    /// With `timestamps`, the first pass writes the beginning timestamp and
+    /// the last pass the end timestamp, timing the whole chain.
+    pub fn encode(&self, encoder: &mut wgpu::CommandEncoder, timestamps: Option<wgpu::RenderPassTimestampWrites<'_>>) {
+        let chain = &self.chain;
+        let passes = (0..BLUR_ITERATIONS).flat_map(|i| {
+            let first = if i == 0 { &chain.from_source } else { &chain.from_b };
+            [(first, &chain.a), (&chain.from_a, &chain.b)]
+        });
+        let last = Self::DRAW_CALLS as usize - 1;
+        for (i, (src, dst)) in passes.enumerate() {
+            let (begin, end) = (i == 0, i == last);
+            let writes = timestamps.as_ref().filter(|_| begin || end).map(|t| wgpu::RenderPassTimestampWrites {
+                query_set: t.query_set,
+                beginning_of_pass_write_index: t.beginning_of_pass_write_index.filter(|_| begin),
+                end_of_pass_write_index: t.end_of_pass_write_index.filter(|_| end),
+            });
+            self.blur(encoder, src, dst, writes);
+        }
+    }
+
+    fn blur(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        src: &wgpu::BindGroup,
+        dst: &wgpu::TextureView,
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites<'_>>,
+    ) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Glow Blur Pass"),
+            color_attachments: &[clear_attachment(dst)],
+            timestamp_writes,
+            ..Default::default()
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, src, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+impl BlurChain {
+    fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        source: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let (w, h) = ((width / 2).max(1), (height / 2).max(1));
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+        let a = create_view(device, "Glow Blur A", w, h, 1, usage);
+        let b = create_view(device, "Glow Blur B", w, h, 1, usage);
+        let horizontal = [1.0 / w as f32, 0.0];
+        let vertical = [0.0, 1.0 / h as f32];
+        let group = |src: &wgpu::TextureView, step: [f32; 2]| blur_group(device, layout, sampler, src, step);
+        Self {
+            from_source: group(source, horizontal),
+            from_a: group(&a, vertical),
+            from_b: group(&b, horizontal),
+            a,
+            b,
+        }
+    }
+}
+
+fn blur_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    src: &wgpu::TextureView,
+    texel_step: [f32; 2],
+) -> wgpu::BindGroup {
+    let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Glow Blur Params"),
+        contents: bytemuck::bytes_of(&BlurParams { texel_step, _pad: [0.0; 2] }),
+        usage: wgpu::BufferUsages::UNIFORM,
+    });
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Glow Blur Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(src) },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(sampler) },
+            wgpu::BindGroupEntry { binding: 2, resource: params.as_entire_binding() },
+        ],
+    })
+}
+
+fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Glow Blur Layout"),
+        entries: &[texture_entry(0), sampler_entry(1), uniform_entry(2)],
+    })
+}
+
+fn create_pipeline(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> wgpu::RenderPipeline {
+    let source = format!("{FULLSCREEN_VS}{BLUR_FS}");
+    fullscreen_pipeline(device, "Glow Blur", &source, "fs_blur", layout, &[HDR_FORMAT], None)
+}
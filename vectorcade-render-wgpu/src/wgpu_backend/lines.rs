@@ -0,0 +1,193 @@
+//! Instanced line rendering with analytic anti-aliasing.
+//!
+//! Each `LineInstance` is expanded in the vertex shader into a quad around
+//! the segment, padded by the stroke radius plus one pixel. The fragment
+//! shader computes the distance to the segment in pixels and turns it into
+//! coverage, which gives round caps and smooth edges without tessellation.
+
+use super::pipeline::scene_targets;
+use crate::tessellate::LineInstance;
+use std::ops::Range;
+use wgpu::util::DeviceExt;
+
+const LINE_SHADER: &str = r#"
+struct Viewport {
+    size: vec2<f32>,
+    _pad: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> viewport: Viewport;
+
+struct LineInput {
+    @location(0) a: vec2<f32>,
+    @location(1) b: vec2<f32>,
+    @location(2) width: f32,
+    @location(3) color: vec4<f32>,
+    @location(4) glow: f32,
+};
+
+struct LineOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) pixel: vec2<f32>,
+    @location(1) @interpolate(flat) a: vec2<f32>,
+    @location(2) @interpolate(flat) b: vec2<f32>,
+    @location(3) @interpolate(flat) radius: f32,
+    @location(4) @interpolate(flat) color: vec4<f32>,
+    @location(5) @interpolate(flat) glow: f32,
+};
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) glow: vec4<f32>,
+};
+
+@vertex
+fn vs_line(@builtin(vertex_index) index: u32, in: LineInput) -> LineOutput {
+    // Two triangles; x selects the end of the segment, y the side.
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[index];
+    let half_size = viewport.size * 0.5;
+    let a = in.a * half_size;
+    let b = in.b * half_size;
+    let radius = in.width * 0.5;
+    let extent = radius + 1.0;
+
+    let d = b - a;
+    let len = length(d);
+    var dir = vec2<f32>(1.0, 0.0);
+    if (len > 1e-6) {
+        dir = d / len;
+    }
+    let normal = vec2<f32>(-dir.y, dir.x);
+    let end = select(a - dir * extent, b + dir * extent, corner.x > 0.0);
+    let pixel = end + normal * extent * corner.y;
+
+    var out: LineOutput;
+    out.clip_position = vec4<f32>(pixel / half_size, 0.0, 1.0);
+    out.pixel = pixel;
+    out.a = a;
+    out.b = b;
+    out.radius = radius;
+    out.color = in.color;
+    out.glow = in.glow;
+    return out;
+}
+
+@fragment
+fn fs_line(in: LineOutput) -> FragmentOutput {
+    let pa = in.pixel - in.a;
+    let ba = in.b - in.a;
+    let h = clamp(dot(pa, ba) / max(dot(ba, ba), 1e-12), 0.0, 1.0);
+    let dist = length(pa - ba * h);
+    let coverage = clamp(in.radius + 0.5 - dist, 0.0, 1.0);
+    let alpha = in.color.a * coverage;
+
+    var out: FragmentOutput;
+    out.color = vec4<f32>(in.color.rgb, alpha);
+    out.glow = vec4<f32>(in.color.rgb * alpha * in.glow, 0.0);
+    return out;
+}
+"#;
+
+/// Pipeline and viewport uniform for instanced lines.
+pub struct LinePipeline {
+    pipeline: wgpu::RenderPipeline,
+    viewport: wgpu::Buffer,
+    group: wgpu::BindGroup,
+}
+
+impl LinePipeline {
+    /// Create the line pipeline for the scene targets at `sample_count`.
+    pub fn new(device: &wgpu::Device, sample_count: u32) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Line Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let viewport = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Line Viewport"),
+            contents: bytemuck::cast_slice(&[0.0f32; 4]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Line Group"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: viewport.as_entire_binding() }],
+        });
+        let pipeline = create_pipeline(device, &layout, sample_count);
+        Self { pipeline, viewport, group }
+    }
+
+    /// Upload the viewport size lines are expanded in.
+    pub fn set_viewport(&self, queue: &wgpu::Queue, width: u32, height: u32) {
+        let data = [width as f32, height as f32, 0.0, 0.0];
+        queue.write_buffer(&self.viewport, 0, bytemuck::cast_slice(&data));
+    }
+
+    /// Draw `instances` from `buffer` into the current scene pass.
+    pub fn draw(&self, pass: &mut wgpu::RenderPass<'_>, buffer: &wgpu::Buffer, instances: Range<u32>) {
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.group, &[]);
+        pass.set_vertex_buffer(0, buffer.slice(..));
+        pass.draw(0..6, instances);
+    }
+}
+
+fn create_pipeline(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, sample_count: u32) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Line Shader"),
+        source: wgpu::ShaderSource::Wgsl(LINE_SHADER.into()),
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Line Pipeline Layout"),
+        bind_group_layouts: &[layout],
+        push_constant_ranges: &[],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Line Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_line"),
+            buffers: &[instance_layout()],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_line"),
+            targets: &scene_targets(),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState { count: sample_count, mask: !0, alpha_to_coverage_enabled: false },
+        multiview: None,
+        cache: None,
+    })
+}
+
+fn instance_layout() -> wgpu::VertexBufferLayout<'static> {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32,
+        3 => Float32x4,
+        4 => Float32,
+    ];
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<LineInstance>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &ATTRIBUTES,
+    }
+}
@@ -0,0 +1,229 @@
+//! Retained meshes: display lists tessellated once and kept in GPU buffers.
+//!
+//! Each mesh owns its vertex and index buffers. Draws queued during a frame
+//! become `MeshInstance`s (transform and tint) in one instance buffer, and
+//! consecutive draws of the same mesh are issued as a single instanced draw.
+
+use super::buffers;
+use crate::RenderStats;
+use crate::commands::CommandTessellator;
+use crate::tessellate::Vertex;
+use glam::Mat3;
+use std::collections::HashMap;
+use std::ops::Range;
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::DrawCmd;
+use wgpu::util::DeviceExt;
+
+/// Handle to a mesh created with [`WgpuRenderer::create_mesh`](super::WgpuRenderer::create_mesh).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MeshId(u32);
+
+/// Per-draw data for the mesh pipeline.
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct MeshInstance {
+    /// Columns of the transform applied to the mesh's NDC positions.
+    transform: [[f32; 3]; 3],
+    /// Multiplied with the vertex colors.
+    tint: [f32; 4],
+}
+
+struct Mesh {
+    cmds: Vec<DrawCmd>,
+    /// `None` for meshes without any geometry.
+    buffers: Option<MeshBuffers>,
+}
+
+struct MeshBuffers {
+    vertex: wgpu::Buffer,
+    index: wgpu::Buffer,
+    index_count: u32,
+}
+
+/// Retained meshes and the draws queued for the next frame.
+pub struct MeshStore {
+    meshes: HashMap<MeshId, Mesh>,
+    next_id: u32,
+    tessellator: CommandTessellator,
+    px_to_ndc: f32,
+    /// World-to-NDC projection meshes are baked with, and its inverse.
+    projection: (Mat3, Mat3),
+    draws: Vec<(MeshId, MeshInstance)>,
+    runs: Vec<(MeshId, Range<u32>)>,
+    instances: Vec<MeshInstance>,
+    instance_buffer: wgpu::Buffer,
+    instance_capacity: usize,
+}
+
+impl MeshStore {
+    const INITIAL_INSTANCES: usize = 256;
+
+    /// Create an empty store tessellating at `px_to_ndc`.
+    pub fn new(device: &wgpu::Device, px_to_ndc: f32) -> Self {
+        let mut tessellator = CommandTessellator::new();
+        tessellator.cache.set_max_age(0);
+        Self {
+            meshes: HashMap::new(),
+            next_id: 0,
+            tessellator,
+            px_to_ndc,
+            projection: (Mat3::IDENTITY, Mat3::IDENTITY),
+            draws: Vec::new(),
+            runs: Vec::new(),
+            instances: Vec::new(),
+            instance_buffer: create_instance_buffer(device, Self::INITIAL_INSTANCES),
+            instance_capacity: Self::INITIAL_INSTANCES,
+        }
+    }
+
+    /// Tessellate `cmds` and upload them as a new mesh.
+    pub fn create(&mut self, device: &wgpu::Device, cmds: &[DrawCmd]) -> MeshId {
+        let id = MeshId(self.next_id);
+        self.next_id += 1;
+        let buffers = self.upload(device, cmds);
+        self.meshes.insert(id, Mesh { cmds: cmds.to_vec(), buffers });
+        id
+    }
+
+    /// Free a mesh, returning whether it existed.
+    pub fn destroy(&mut self, id: MeshId) -> bool {
+        self.draws.retain(|(draw, _)| *draw != id);
+        self.meshes.remove(&id).is_some()
+    }
+
+    /// Whether `id` refers to a live mesh.
+    pub fn contains(&self, id: MeshId) -> bool {
+        self.meshes.contains_key(&id)
+    }
+
+    /// Re-tessellate all meshes if the pixel size or the projection changed,
+    /// keeping stroke widths in pixels.
+    pub fn set_projection(&mut self, device: &wgpu::Device, px_to_ndc: f32, projection: Mat3) {
+        if px_to_ndc == self.px_to_ndc && projection == self.projection.0 {
+            return;
+        }
+        self.px_to_ndc = px_to_ndc;
+        self.projection = (projection, projection.inverse());
+        let ids: Vec<MeshId> = self.meshes.keys().copied().collect();
+        for id in ids {
+            let cmds = std::mem::take(&mut self.meshes.get_mut(&id).expect("live mesh").cmds);
+            let buffers = self.upload(device, &cmds);
+            self.meshes.insert(id, Mesh { cmds, buffers });
+        }
+    }
+
+    /// Queue a draw of `id` for the next frame. Unknown ids are ignored.
+    ///
+    /// `transform` is in world units; it is applied to the baked NDC
+    /// vertices between the inverse projection and the projection.
+    pub fn queue(&mut self, id: MeshId, transform: Mat3, tint: Rgba) {
+        if self.contains(id) {
+            let (projection, inverse) = self.projection;
+            let instance = MeshInstance {
+                transform: (projection * transform * inverse).to_cols_array_2d(),
+                tint: [tint.0, tint.1, tint.2, tint.3],
+            };
+            self.draws.push((id, instance));
+        }
+    }
+
+    /// Upload the queued draws and group them into instanced runs, emptying the queue.
+    ///
+    /// Returns the number of draws.
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, stats: &mut RenderStats) -> u32 {
+        self.runs.clear();
+        self.instances.clear();
+        for (i, (id, instance)) in self.draws.drain(..).enumerate() {
+            let i = i as u32;
+            match self.runs.last_mut() {
+                Some((last, range)) if *last == id => range.end = i + 1,
+                _ => self.runs.push((id, i..i + 1)),
+            }
+            self.instances.push(instance);
+        }
+        if self.instances.len() > self.instance_capacity {
+            self.instance_capacity = self.instances.len().next_power_of_two();
+            self.instance_buffer = create_instance_buffer(device, self.instance_capacity);
+            stats.buffer_reallocations += 1;
+        }
+        buffers::write(queue, &self.instance_buffer, bytemuck::cast_slice(&self.instances), stats);
+        self.instances.len() as u32
+    }
+
+    /// Draw the runs from the last [`prepare`](Self::prepare) with the mesh
+    /// pipeline and the transform bind `group`.
+    ///
+    /// Returns the number of draw calls.
+    pub fn draw(
+        &self,
+        pass: &mut wgpu::RenderPass<'_>,
+        pipeline: &wgpu::RenderPipeline,
+        group: &wgpu::BindGroup,
+    ) -> u32 {
+        if self.runs.is_empty() {
+            return 0;
+        }
+        let mut draw_calls = 0;
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, group, &[]);
+        pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        for (id, instances) in &self.runs {
+            let Some(buffers) = self.meshes.get(id).and_then(|mesh| mesh.buffers.as_ref()) else {
+                continue;
+            };
+            pass.set_vertex_buffer(0, buffers.vertex.slice(..));
+            pass.set_index_buffer(buffers.index.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..buffers.index_count, 0, instances.clone());
+            draw_calls += 1;
+        }
+        draw_calls
+    }
+
+    fn upload(&mut self, device: &wgpu::Device, cmds: &[DrawCmd]) -> Option<MeshBuffers> {
+        self.tessellator.state.set_projection(self.projection.0);
+        self.tessellator.tessellate(cmds, self.px_to_ndc);
+        let geometry = &self.tessellator.geometry;
+        if geometry.indices.is_empty() {
+            return None;
+        }
+        // Bake transforms inside `cmds` into the vertices; the mesh pipeline
+        // only applies the per-draw transform and the target's offset scale.
+        let vertices: Vec<Vertex> = geometry.vertices.iter().map(|v| geometry.bake(v)).collect();
+        let vertex = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Mesh Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Mesh Index Buffer"),
+            contents: bytemuck::cast_slice(&geometry.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        Some(MeshBuffers { vertex, index, index_count: geometry.indices.len() as u32 })
+    }
+}
+
+/// Vertex buffer layout of `MeshInstance`, at shader locations 5-8.
+pub fn instance_layout() -> wgpu::VertexBufferLayout<'static> {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        5 => Float32x3,
+        6 => Float32x3,
+        7 => Float32x3,
+        8 => Float32x4,
+    ];
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<MeshInstance>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &ATTRIBUTES,
+    }
+}
+
+fn create_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Mesh Instance Buffer"),
+        size: (capacity * std::mem::size_of::<MeshInstance>()) as u64,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
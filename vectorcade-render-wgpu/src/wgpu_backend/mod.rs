@@ -4,68 +4,222 @@
 //! using wgpu (WebGPU in browser, Vulkan/Metal/DX12 native).
 
 mod buffers;
+#[cfg(not(target_arch = "wasm32"))]
+mod capture;
+mod composite;
+mod config;
+mod error;
+mod fullscreen;
+mod glow;
+mod lines;
+mod meshes;
+mod persistence;
 mod pipeline;
-mod state;
-mod text;
+mod profiler;
+mod render;
+mod target;
+mod targets;
+mod transforms;
+mod viewport;
 
 use buffers::BufferPool;
-use crate::tessellate::{Geometry, tessellate_line, tessellate_polyline};
-use crate::{RenderStats, VectorRenderer};
-use state::RenderState;
-use vectorcade_fonts::{AtariMini, Cinematronics, FontRegistry, Midway, VectorScanline};
+use composite::Composite;
+use crate::CoordinateSystem;
+use crate::commands::CommandTessellator;
+use glam::Mat3;
+use glow::GlowPass;
+use lines::LinePipeline;
+use meshes::MeshStore;
+use persistence::Persistence;
+use pipeline::Gpu;
+use profiler::GpuProfiler;
+use target::{HEADLESS_FORMAT, RenderTarget};
+use targets::SceneTargets;
+use transforms::TransformBuffer;
+use vectorcade_shared::Rgba;
 use vectorcade_shared::draw::DrawCmd;
 
+pub use composite::LoadMode;
+pub use config::RendererConfig;
+pub use error::RenderError;
+pub use meshes::MeshId;
+pub use persistence::P7_DECAY;
+pub use pipeline::DEFAULT_SAMPLE_COUNT;
+pub use profiler::GpuTimings;
+pub use viewport::{ViewportMode, ViewportRect};
+
+/// Default global phosphor glow intensity.
+const DEFAULT_GLOW_INTENSITY: f32 = 1.0;
+
 /// GPU renderer using wgpu.
+///
+/// The scene is rendered into offscreen HDR targets, optionally accumulated with
+/// the previous frames for phosphor persistence, the glow target is blurred into
+/// a bloom, and both are composited onto the surface (or, for headless
+/// renderers, an owned texture).
 pub struct WgpuRenderer {
     device: wgpu::Device,
     queue: wgpu::Queue,
     pipeline: wgpu::RenderPipeline,
-    surface: wgpu::Surface<'static>,
-    config: wgpu::SurfaceConfiguration,
-    msaa_view: wgpu::TextureView,
+    transforms: TransformBuffer,
+    lines: LinePipeline,
+    mesh_pipeline: wgpu::RenderPipeline,
+    meshes: MeshStore,
+    sample_count: u32,
+    sample_counts: Vec<u32>,
+    target: RenderTarget,
+    /// How the game's square maps onto `target`; the scene targets are its rect's size.
+    viewport: ViewportMode,
+    /// Convention of display-list points, projected onto the viewport rect.
+    coords: CoordinateSystem,
+    scene: SceneTargets,
+    glow: GlowPass,
+    composite: Composite,
+    persistence: Option<Persistence>,
+    glow_intensity: f32,
     buffers: BufferPool,
-    fonts: FontRegistry,
-    geometry: Geometry,
-    state: RenderState,
+    commands: CommandTessellator,
+    /// Pass timing, when enabled with `set_gpu_profiling`.
+    profiler: Option<GpuProfiler>,
 }
 
 impl WgpuRenderer {
     /// Create a new renderer for the given window.
     ///
+    /// `config` selects the adapter, presentation and MSAA; see [`RendererConfig`].
+    ///
     /// # Errors
-    /// Returns an error if GPU initialization fails.
+    /// Returns an error if the surface cannot be created, no adapter is
+    /// available, device creation fails or the surface has no usable format.
     pub async fn new(
         window: impl Into<wgpu::SurfaceTarget<'static>>,
         width: u32,
         height: u32,
-    ) -> Result<Self, String> {
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
-        let surface = instance.create_surface(window).map_err(|e| e.to_string())?;
-        let (device, queue, config) = pipeline::init_device(&instance, &surface, width, height).await?;
-        let surface_format = config.format;
-        surface.configure(&device, &config);
-        let pipeline = pipeline::create(&device, surface_format);
-
-        let msaa_view = pipeline::create_msaa_texture(&device, surface_format, width, height);
+        config: RendererConfig,
+    ) -> Result<Self, RenderError> {
+        let instance = pipeline::create_instance(config.backends);
+        let surface = instance.create_surface(window)?;
+        let (gpu, surface_config, modes) = pipeline::init_device(&instance, &surface, width, height, &config).await?;
+        surface.configure(&gpu.device, &surface_config);
+        let target = RenderTarget::Surface { surface, config: surface_config, modes };
+        Ok(Self::from_parts(gpu, target, config.sample_count))
+    }
+
+    /// Create a new renderer for the given window with a requested MSAA sample count.
+    ///
+    /// Counts the adapter does not support fall back to the closest lower
+    /// supported count; see [`WgpuRenderer::sample_count`].
+    ///
+    /// # Errors
+    /// Returns an error if GPU initialization fails.
+    pub async fn new_with_sample_count(
+        window: impl Into<wgpu::SurfaceTarget<'static>>,
+        width: u32,
+        height: u32,
+        sample_count: u32,
+    ) -> Result<Self, RenderError> {
+        Self::new(window, width, height, RendererConfig { sample_count, ..RendererConfig::default() }).await
+    }
+
+    /// Create a renderer that draws into its own offscreen texture instead of a window.
+    ///
+    /// Uses the default adapter if one exists and the software fallback adapter
+    /// otherwise. Frames are rendered with the same pipeline as windowed
+    /// renderers; the result stays in [`WgpuRenderer::target_texture`].
+    ///
+    /// # Errors
+    /// Returns an error if no adapter is available or device creation fails.
+    pub async fn new_headless(width: u32, height: u32) -> Result<Self, RenderError> {
+        Self::new_headless_with_config(width, height, RendererConfig::default()).await
+    }
+
+    /// Create a headless renderer with a requested MSAA sample count.
+    ///
+    /// # Errors
+    /// Returns an error if no adapter is available or device creation fails.
+    pub async fn new_headless_with_sample_count(
+        width: u32,
+        height: u32,
+        sample_count: u32,
+    ) -> Result<Self, RenderError> {
+        let config = RendererConfig { sample_count, ..RendererConfig::default() };
+        Self::new_headless_with_config(width, height, config).await
+    }
+
+    /// Create a headless renderer with the adapter and MSAA options of `config`.
+    ///
+    /// The presentation options have no effect without a surface.
+    ///
+    /// # Errors
+    /// Returns an error if no adapter is available or device creation fails.
+    pub async fn new_headless_with_config(
+        width: u32,
+        height: u32,
+        config: RendererConfig,
+    ) -> Result<Self, RenderError> {
+        let instance = pipeline::create_instance(config.backends);
+        let gpu = pipeline::init_headless_device(&instance, config.power_preference).await?;
+        let target = RenderTarget::texture(&gpu.device, width.max(1), height.max(1), HEADLESS_FORMAT);
+        Ok(Self::from_parts(gpu, target, config.sample_count))
+    }
+
+    /// Create a renderer on a device and queue owned by other wgpu code.
+    ///
+    /// Lets the vector renderer share one GPU context with, say, a menu UI.
+    /// `Device` and `Queue` are reference-counted handles, so pass clones to
+    /// keep using them. Frames are rendered into an owned texture of `format`,
+    /// available from [`target_texture`](Self::target_texture); `format`
+    /// must support `RENDER_ATTACHMENT`, `TEXTURE_BINDING` and `COPY_SRC`.
+    ///
+    /// Without the adapter only the MSAA counts WebGPU guarantees (1 and 4)
+    /// are offered, and GPU profiling needs `TIMESTAMP_QUERY` enabled on `device`.
+    pub fn from_device(
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let target = RenderTarget::texture(&device, width.max(1), height.max(1), format);
+        Self::from_parts(Gpu::shared(device, queue), target, DEFAULT_SAMPLE_COUNT)
+    }
+
+    fn from_parts(gpu: Gpu, target: RenderTarget, sample_count: u32) -> Self {
+        let Gpu { device, queue, sample_counts } = gpu;
+        let sample_count = pipeline::pick_sample_count(sample_count, &sample_counts);
+        let (width, height) = target.size();
+        let transforms = TransformBuffer::new(&device);
+        let pipeline = pipeline::create(&device, sample_count, transforms.layout());
+        let lines = LinePipeline::new(&device, sample_count);
+        let mesh_pipeline = pipeline::create_mesh(&device, sample_count, transforms.layout());
+        let meshes = MeshStore::new(&device, 2.0 / height as f32);
+        let scene = SceneTargets::new(&device, width, height, sample_count);
+        let glow = GlowPass::new(&device, &scene.glow, width, height);
+        let composite = Composite::new(&device, target.format(), &scene.color, glow.output());
         let buffers = BufferPool::new(&device);
-        let mut fonts = FontRegistry::new();
-        fonts.register(AtariMini);
-        fonts.register(Cinematronics);
-        fonts.register(Midway);
-        fonts.register(VectorScanline);
 
-        Ok(Self {
+        Self {
             device,
             queue,
             pipeline,
-            surface,
-            config,
-            msaa_view,
+            transforms,
+            lines,
+            mesh_pipeline,
+            meshes,
+            sample_count,
+            sample_counts,
+            target,
+            viewport: ViewportMode::default(),
+            coords: CoordinateSystem::default(),
+            scene,
+            glow,
+            composite,
+            persistence: None,
+            glow_intensity: DEFAULT_GLOW_INTENSITY,
             buffers,
-            fonts,
-            geometry: Geometry::new(),
-            state: RenderState::default(),
-        })
+            commands: CommandTessellator::new(),
+            profiler: None,
+        }
     }
 
     /// Create a new renderer for an HTML canvas element (WASM only).
@@ -77,116 +231,214 @@ impl WgpuRenderer {
         canvas: web_sys::HtmlCanvasElement,
         width: u32,
         height: u32,
-    ) -> Result<Self, String> {
-        Self::new(wgpu::SurfaceTarget::Canvas(canvas), width, height).await
+        config: RendererConfig,
+    ) -> Result<Self, RenderError> {
+        Self::new(wgpu::SurfaceTarget::Canvas(canvas), width, height, config).await
     }
 
-    /// Resize the render surface.
+    /// Resize the render surface (or the headless target texture).
     pub fn resize(&mut self, width: u32, height: u32) {
         if width > 0 && height > 0 {
-            self.config.width = width;
-            self.config.height = height;
-            self.surface.configure(&self.device, &self.config);
-            self.msaa_view = pipeline::create_msaa_texture(&self.device, self.config.format, width, height);
+            self.target.resize(&self.device, width, height);
+            self.update_meshes();
+            self.recreate_scene();
         }
     }
-}
 
-impl VectorRenderer for WgpuRenderer {
-    fn render(&mut self, cmds: &[DrawCmd]) -> RenderStats {
-        self.state.reset();
-        let stats = self.tessellate_commands(cmds);
+    /// Current MSAA sample count (1 means no multisampling).
+    #[must_use]
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
 
-        let Some(frame) = self.begin_frame() else {
-            return stats;
-        };
+    /// MSAA sample counts supported on this device, in ascending order.
+    ///
+    /// Always includes 1 and 4 on WebGPU-conformant devices; 2, 8 and 16 are
+    /// listed when the adapter exposes them.
+    #[must_use]
+    pub fn supported_sample_counts(&self) -> &[u32] {
+        &self.sample_counts
+    }
 
-        self.buffers.update(&self.device, &self.queue, &self.geometry.vertices, &self.geometry.indices);
-        self.draw_frame(&frame);
-        frame.output.present();
-        stats
+    /// Switch the MSAA sample count, recreating the scene pipeline and targets.
+    ///
+    /// Unsupported counts fall back to the closest lower supported count.
+    /// Returns the count now in use. Persistence trails are reset.
+    pub fn set_sample_count(&mut self, sample_count: u32) -> u32 {
+        let sample_count = pipeline::pick_sample_count(sample_count, &self.sample_counts);
+        if sample_count != self.sample_count {
+            self.sample_count = sample_count;
+            self.pipeline = pipeline::create(&self.device, sample_count, self.transforms.layout());
+            self.lines = LinePipeline::new(&self.device, sample_count);
+            self.mesh_pipeline = pipeline::create_mesh(&self.device, sample_count, self.transforms.layout());
+            self.recreate_scene();
+        }
+        sample_count
     }
-}
 
-impl WgpuRenderer {
-    /// Calculate pixel to NDC conversion factor.
-    fn px_to_ndc(&self) -> f32 {
-        2.0 / self.config.height as f32
-    }
-
-    fn tessellate_commands(&mut self, cmds: &[DrawCmd]) -> RenderStats {
-        let mut stats = RenderStats::default();
-        self.geometry.clear();
-        let px_to_ndc = self.px_to_ndc();
-
-        for cmd in cmds {
-            match cmd {
-                DrawCmd::Clear { color } => self.state.clear = *color,
-                DrawCmd::PushTransform(t) => self.state.push(*t),
-                DrawCmd::PopTransform => self.state.pop(),
-                DrawCmd::Line(line) => {
-                    let t = self.state.transform_opt();
-                    tessellate_line(line, t.as_ref(), px_to_ndc, &mut self.geometry);
-                    stats.lines += 1;
-                }
-                DrawCmd::Polyline {
-                    pts,
-                    closed,
-                    stroke,
-                } => {
-                    let t = self.state.transform_opt();
-                    let pts: Vec<[f32; 2]> = pts.iter().map(|v| [v.x, v.y]).collect();
-                    tessellate_polyline(&pts, *closed, stroke, t.as_ref(), px_to_ndc, &mut self.geometry);
-                    stats.polylines += 1;
+    /// The wgpu device used for rendering.
+    #[must_use]
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    /// The wgpu queue used for rendering.
+    #[must_use]
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    /// The offscreen texture frames are rendered into, for headless and
+    /// [`from_device`](Self::from_device) renderers.
+    ///
+    /// Has `COPY_SRC` and `TEXTURE_BINDING` usage. Returns `None` for
+    /// renderers that draw to a window surface.
+    #[must_use]
+    pub fn target_texture(&self) -> Option<&wgpu::Texture> {
+        match &self.target {
+            RenderTarget::Texture(texture) => Some(texture),
+            RenderTarget::Surface { .. } => None,
+        }
+    }
+
+    /// Current render target size in pixels.
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        self.target.size()
+    }
+
+    /// Set the global phosphor glow intensity.
+    ///
+    /// Each stroke contributes to the bloom in proportion to its `Stroke::glow`;
+    /// this scales the whole bloom. `0.0` disables the blur passes entirely.
+    pub fn set_glow_intensity(&mut self, intensity: f32) {
+        self.glow_intensity = intensity.max(0.0);
+    }
+
+    /// Current global phosphor glow intensity.
+    #[must_use]
+    pub fn glow_intensity(&self) -> f32 {
+        self.glow_intensity
+    }
+
+    /// Draw `DrawCmd::Line` with the instanced line pipeline instead of lyon tessellation.
+    ///
+    /// Each segment is uploaded as one 40-byte instance and expanded into a
+    /// capsule on the GPU, with coverage computed from the distance to the
+    /// segment. Polylines and text are still tessellated; draw order is kept.
+    pub fn set_instanced_lines(&mut self, enabled: bool) {
+        self.commands.instanced_lines = enabled;
+    }
+
+    /// Whether lines use the instanced pipeline.
+    #[must_use]
+    pub fn instanced_lines(&self) -> bool {
+        self.commands.instanced_lines
+    }
+
+    /// Keep cached polyline meshes for `frames` frames after their last use; 0 disables the cache.
+    ///
+    /// Polylines drawn again with the same points and stroke reuse their
+    /// tessellated mesh under any transform. The default is 60 frames.
+    pub fn set_cache_max_age(&mut self, frames: u32) {
+        self.commands.cache.set_max_age(frames);
+    }
+
+    /// Frames an unused cached mesh is kept.
+    #[must_use]
+    pub fn cache_max_age(&self) -> u32 {
+        self.commands.cache.max_age()
+    }
+
+    /// Tessellate display lists of 1024 or more commands on rayon's thread pool (default on).
+    ///
+    /// The output is identical to serial tessellation; only cache hit counts
+    /// may differ, as chunks do not see each other's new meshes.
+    #[cfg(feature = "parallel")]
+    pub fn set_parallel(&mut self, enabled: bool) {
+        self.commands.parallel = enabled;
+    }
+
+    /// Tessellate `cmds` once and keep the result in GPU buffers.
+    ///
+    /// The mesh can then be drawn with [`draw_mesh`](Self::draw_mesh) any
+    /// number of times per frame without re-tessellating or re-uploading it.
+    /// Transforms inside `cmds` are applied at creation and `Clear` is ignored.
+    /// Stroke widths are in pixels; meshes are re-tessellated on resize and
+    /// when the coordinate system changes.
+    pub fn create_mesh(&mut self, cmds: &[DrawCmd]) -> MeshId {
+        self.meshes.create(&self.device, cmds)
+    }
+
+    /// Queue mesh `id` for the next frame with `transform` and `tint`.
+    ///
+    /// `transform` is applied to the mesh's world positions and `tint` is
+    /// multiplied with its colors. Queued meshes are drawn in call order
+    /// beneath the display list of the next `render`. Unlike `PushTransform`,
+    /// scaling a mesh also scales its stroke widths. Unknown ids are ignored.
+    pub fn draw_mesh(&mut self, id: MeshId, transform: Mat3, tint: Rgba) {
+        self.meshes.queue(id, transform, tint);
+    }
+
+    /// Free mesh `id` and its GPU buffers, returning whether it existed.
+    pub fn destroy_mesh(&mut self, id: MeshId) -> bool {
+        self.meshes.destroy(id)
+    }
+
+    /// Enable phosphor persistence with a per-frame RGB decay, or disable it with `None`.
+    ///
+    /// Each frame the accumulated image is multiplied by `decay` (clamped to
+    /// `0.0..=1.0`) before the new frame is added, so moving strokes leave
+    /// fading trails and strokes held still brighten. [`P7_DECAY`]
+    /// approximates a P7 phosphor at 60 Hz. `None` also drops the trails.
+    pub fn set_persistence(&mut self, decay: Option<[f32; 3]>) {
+        match decay {
+            Some(decay) => {
+                if let Some(persistence) = &mut self.persistence {
+                    persistence.set_decay(decay);
+                    return;
                 }
-                DrawCmd::Text { pos, text, size_px, color, style } => {
-                    let t = self.state.transform_opt();
-                    let params = text::TextParams {
-                        registry: &self.fonts, text, pos: *pos, size_px: *size_px, color: *color, style: *style, px_to_ndc,
-                    };
-                    text::tessellate_text(&params, t.as_ref(), &mut self.geometry);
-                    stats.text_runs += 1;
+                let (w, h) = self.scene_size();
+                self.persistence = Some(Persistence::new(&self.device, &self.scene, w, h, decay));
+                self.rebind_post();
+            }
+            None => {
+                if self.persistence.take().is_some() {
+                    self.rebind_post();
                 }
-                _ => {}
             }
         }
-        stats
-    }
-
-    fn begin_frame(&self) -> Option<Frame> {
-        let output = self.surface.get_current_texture().ok()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-        Some(Frame { output, view })
-    }
-
-    fn draw_frame(&self, frame: &Frame) {
-        let mut encoder = self.device.create_command_encoder(&Default::default());
-        {
-            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &self.msaa_view,
-                    resolve_target: Some(&frame.view),
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(self.state.clear_color()),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                ..Default::default()
-            });
-
-            pass.set_pipeline(&self.pipeline);
-            pass.set_vertex_buffer(0, self.buffers.vertex.slice(..));
-            pass.set_index_buffer(self.buffers.index.slice(..), wgpu::IndexFormat::Uint32);
-            pass.draw_indexed(0..self.geometry.indices.len() as u32, 0, 0..1);
+    }
+
+    /// Current persistence decay, or `None` if persistence is off.
+    #[must_use]
+    pub fn persistence(&self) -> Option<[f32; 3]> {
+        self.persistence.as_ref().map(Persistence::decay)
+    }
+
+    /// Calculate pixel to NDC conversion factor, vertically in the scene.
+    fn px_to_ndc(&self) -> f32 {
+        2.0 / self.scene_size().1 as f32
+    }
+
+    /// Recreate the scene targets for the current scene size and sample count.
+    fn recreate_scene(&mut self) {
+        let (width, height) = self.scene_size();
+        self.scene = SceneTargets::new(&self.device, width, height, self.sample_count);
+        if let Some(persistence) = &mut self.persistence {
+            persistence.resize(&self.device, &self.scene, width, height);
         }
-        self.queue.submit(std::iter::once(encoder.finish()));
+        self.rebind_post();
     }
-}
 
-struct Frame {
-    output: wgpu::SurfaceTexture,
-    view: wgpu::TextureView,
+    /// Point the glow and composite passes at the scene or the persistence output.
+    fn rebind_post(&mut self) {
+        let (color, glow) = match &self.persistence {
+            Some(p) => (p.color(), p.glow()),
+            None => (&self.scene.color, &self.scene.glow),
+        };
+        let (width, height) = self.scene_size();
+        self.glow.rebind(&self.device, glow, width, height);
+        self.composite.rebind(&self.device, color, self.glow.output());
+    }
 }
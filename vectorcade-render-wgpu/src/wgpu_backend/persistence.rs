@@ -0,0 +1,203 @@
+//! Phosphor persistence: a feedback buffer that leaves decaying trails.
+//!
+//! Each frame the previous result is multiplied by a per-channel decay factor
+//! and the new scene is added on top, so overlapping trails brighten and a
+//! stroke held still builds up towards `1 / (1 - decay)` times its color. Both
+//! the color and the glow target are accumulated, so trails bloom as well.
+
+use super::fullscreen::{
+    FULLSCREEN_VS, create_linear_sampler, fullscreen_pipeline, sampler_entry, texture_entry, uniform_entry,
+};
+use super::pipeline::HDR_FORMAT;
+use super::targets::{SceneTargets, clear_attachment, create_texture};
+
+/// Accumulate the scene over the decayed history into two HDR targets.
+const PERSISTENCE_FS: &str = r#"
+struct PersistenceParams {
+    decay: vec4<f32>,
+};
+
+struct PersistenceOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) glow: vec4<f32>,
+};
+
+@group(0) @binding(0) var scene_color: texture_2d<f32>;
+@group(0) @binding(1) var scene_glow: texture_2d<f32>;
+@group(0) @binding(2) var history_color: texture_2d<f32>;
+@group(0) @binding(3) var history_glow: texture_2d<f32>;
+@group(0) @binding(4) var linear_sampler: sampler;
+@group(0) @binding(5) var<uniform> params: PersistenceParams;
+
+@fragment
+fn fs_persist(in: FullscreenOutput) -> PersistenceOutput {
+    let color = textureSample(scene_color, linear_sampler, in.uv);
+    let glow = textureSample(scene_glow, linear_sampler, in.uv);
+    let prev_color = textureSample(history_color, linear_sampler, in.uv).rgb * params.decay.rgb;
+    let prev_glow = textureSample(history_glow, linear_sampler, in.uv).rgb * params.decay.rgb;
+    var out: PersistenceOutput;
+    out.color = vec4<f32>(prev_color + color.rgb, color.a);
+    out.glow = vec4<f32>(prev_glow + glow.rgb, 0.0);
+    return out;
+}
+"#;
+
+/// Decay factors approximating P7 phosphor: a fast blue flash and a long yellow-green tail.
+pub const P7_DECAY: [f32; 3] = [0.90, 0.92, 0.75];
+
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct PersistenceParams {
+    decay: [f32; 4],
+}
+
+/// Accumulation pass and its ping-pong textures.
+pub struct Persistence {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    params: wgpu::Buffer,
+    targets: PersistenceTargets,
+    decay: [f32; 3],
+}
+
+/// Accumulated output and the copy of the previous frame it is built from.
+struct PersistenceTargets {
+    color: wgpu::Texture,
+    glow: wgpu::Texture,
+    color_view: wgpu::TextureView,
+    glow_view: wgpu::TextureView,
+    history_color: wgpu::Texture,
+    history_glow: wgpu::Texture,
+    group: wgpu::BindGroup,
+}
+
+impl Persistence {
+    /// Create the persistence pass reading from `scene`.
+    pub fn new(device: &wgpu::Device, scene: &SceneTargets, width: u32, height: u32, decay: [f32; 3]) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Persistence Layout"),
+            entries: &[
+                texture_entry(0),
+                texture_entry(1),
+                texture_entry(2),
+                texture_entry(3),
+                sampler_entry(4),
+                uniform_entry(5),
+            ],
+        });
+        let pipeline = create_pipeline(device, &layout);
+        let sampler = create_linear_sampler(device);
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Persistence Params"),
+            size: std::mem::size_of::<PersistenceParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let targets = PersistenceTargets::new(device, &layout, &sampler, &params, scene, width, height);
+        Self { pipeline, layout, sampler, params, targets, decay }
+    }
+
+    /// Recreate the accumulation textures for a new scene size, dropping the history.
+    pub fn resize(&mut self, device: &wgpu::Device, scene: &SceneTargets, width: u32, height: u32) {
+        self.targets = PersistenceTargets::new(device, &self.layout, &self.sampler, &self.params, scene, width, height);
+    }
+
+    /// Per-channel (RGB) decay applied to the previous frame.
+    pub fn decay(&self) -> [f32; 3] {
+        self.decay
+    }
+
+    /// Set the per-channel (RGB) decay applied to the previous frame.
+    pub fn set_decay(&mut self, decay: [f32; 3]) {
+        self.decay = decay;
+    }
+
+    /// Accumulated color, the composite input while persistence is on.
+    pub fn color(&self) -> &wgpu::TextureView {
+        &self.targets.color_view
+    }
+
+    /// Accumulated glow, the blur input while persistence is on.
+    pub fn glow(&self) -> &wgpu::TextureView {
+        &self.targets.glow_view
+    }
+
+    /// Record the accumulation pass and save its result as the next frame's history.
+    pub fn encode(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites<'_>>,
+    ) {
+        let [r, g, b] = self.decay.map(|d| d.clamp(0.0, 1.0));
+        let params = PersistenceParams { decay: [r, g, b, 0.0] };
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+
+        let t = &self.targets;
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Persistence Pass"),
+                color_attachments: &[clear_attachment(&t.color_view), clear_attachment(&t.glow_view)],
+                timestamp_writes,
+                ..Default::default()
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &t.group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+        encoder.copy_texture_to_texture(t.color.as_image_copy(), t.history_color.as_image_copy(), t.color.size());
+        encoder.copy_texture_to_texture(t.glow.as_image_copy(), t.history_glow.as_image_copy(), t.glow.size());
+    }
+}
+
+impl PersistenceTargets {
+    fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        params: &wgpu::Buffer,
+        scene: &SceneTargets,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let output = wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC;
+        let history = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        let color = create_texture(device, "Persistence Color", width, height, 1, output);
+        let glow = create_texture(device, "Persistence Glow", width, height, 1, output);
+        let history_color = create_texture(device, "Persistence History Color", width, height, 1, history);
+        let history_glow = create_texture(device, "Persistence History Glow", width, height, 1, history);
+
+        let view = |t: &wgpu::Texture| t.create_view(&wgpu::TextureViewDescriptor::default());
+        let (history_color_view, history_glow_view) = (view(&history_color), view(&history_glow));
+        let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Persistence Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&scene.color) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&scene.glow) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&history_color_view) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&history_glow_view) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::Sampler(sampler) },
+                wgpu::BindGroupEntry { binding: 5, resource: params.as_entire_binding() },
+            ],
+        });
+
+        Self {
+            color_view: view(&color),
+            glow_view: view(&glow),
+            color,
+            glow,
+            history_color,
+            history_glow,
+            group,
+        }
+    }
+}
+
+fn create_pipeline(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> wgpu::RenderPipeline {
+    let source = format!("{FULLSCREEN_VS}{PERSISTENCE_FS}");
+    fullscreen_pipeline(device, "Persistence", &source, "fs_persist", layout, &[HDR_FORMAT, HDR_FORMAT], None)
+}
@@ -1,66 +1,180 @@
 //! Render pipeline creation for wgpu backend.
 
+use super::RenderError;
+use super::config::{RendererConfig, SurfaceModes};
 use crate::tessellate::Vertex;
 
-/// MSAA sample count for anti-aliasing.
-pub const SAMPLE_COUNT: u32 = 4;
+/// Default MSAA sample count for anti-aliasing.
+pub const DEFAULT_SAMPLE_COUNT: u32 = 4;
+
+/// MSAA sample counts guaranteed for `HDR_FORMAT` by WebGPU.
+const WEBGPU_SAMPLE_COUNTS: [u32; 2] = [1, 4];
+
+/// Format of the offscreen scene and glow targets.
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Additive blending so overlapping glow contributions accumulate.
+const ADDITIVE_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent::REPLACE,
+};
 
 /// WGSL shader source for vertex and fragment stages.
 pub const SHADER_SOURCE: &str = r#"
 struct VertexInput {
     @location(0) position: vec2<f32>,
-    @location(1) color: vec4<f32>,
+    @location(1) offset: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) glow: f32,
+    @location(4) transform: u32,
+};
+
+struct MeshInstance {
+    @location(5) transform_0: vec3<f32>,
+    @location(6) transform_1: vec3<f32>,
+    @location(7) transform_2: vec3<f32>,
+    @location(8) tint: vec4<f32>,
+};
+
+struct OffsetScale {
+    scale: vec2<f32>,
+    unproject: vec2<f32>,
 };
 
+@group(0) @binding(0) var<storage, read> transforms: array<mat3x3<f32>>;
+@group(0) @binding(1) var<uniform> offset_scale: OffsetScale;
+
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(1) glow: f32,
+};
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) glow: vec4<f32>,
 };
 
 @vertex
 fn vs_main(in: VertexInput) -> VertexOutput {
+    // Transform the centerline point, then add the edge offset rotated but
+    // not scaled, by neither the transform nor the projection, so stroke
+    // widths stay in pixels.
+    let transform = transforms[in.transform];
+    let unproject = offset_scale.unproject;
+    let linear = mat2x2<f32>(transform[0].xy * unproject, transform[1].xy * unproject);
+    let scale = sqrt(abs(determinant(linear)));
+    var offset = in.offset;
+    if (scale > 0.0) {
+        offset = linear * in.offset / scale;
+    }
+    let position = (transform * vec3<f32>(in.position, 1.0)).xy + offset * offset_scale.scale;
+
     var out: VertexOutput;
-    out.clip_position = vec4<f32>(in.position, 0.0, 1.0);
+    out.clip_position = vec4<f32>(position, 0.0, 1.0);
     out.color = in.color;
+    out.glow = in.glow;
+    return out;
+}
+
+@vertex
+fn vs_mesh(in: VertexInput, instance: MeshInstance) -> VertexOutput {
+    // The instance transform scales stroke widths along with the mesh.
+    let transform = mat3x3<f32>(instance.transform_0, instance.transform_1, instance.transform_2);
+    let linear = mat2x2<f32>(transform[0].xy, transform[1].xy);
+    let position = (transform * vec3<f32>(in.position, 1.0)).xy + linear * (in.offset * offset_scale.scale);
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(position, 0.0, 1.0);
+    out.color = in.color * instance.tint;
+    out.glow = in.glow;
     return out;
 }
 
 @fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    var out: FragmentOutput;
+    out.color = in.color;
+    out.glow = vec4<f32>(in.color.rgb * in.color.a * in.glow, 0.0);
+    return out;
 }
 "#;
 
-/// Create the render pipeline.
-pub fn create(device: &wgpu::Device, format: wgpu::TextureFormat) -> wgpu::RenderPipeline {
-    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Shader"),
-        source: wgpu::ShaderSource::Wgsl(SHADER_SOURCE.into()),
-    });
+/// A device and queue plus the MSAA sample counts the scene targets support on it.
+pub struct Gpu {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub sample_counts: Vec<u32>,
+}
+
+impl Gpu {
+    /// A device created elsewhere. Without its adapter only WebGPU's
+    /// guaranteed sample counts are known.
+    pub fn shared(device: wgpu::Device, queue: wgpu::Queue) -> Self {
+        Self { device, queue, sample_counts: WEBGPU_SAMPLE_COUNTS.to_vec() }
+    }
+}
 
+/// Create the scene pipeline, which writes stroke color and glow into two HDR targets.
+///
+/// `transforms` is the layout of the storage buffer indexed by `Vertex::transform`.
+pub fn create(
+    device: &wgpu::Device,
+    sample_count: u32,
+    transforms: &wgpu::BindGroupLayout,
+) -> wgpu::RenderPipeline {
     let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Pipeline Layout"),
-        bind_group_layouts: &[],
+        bind_group_layouts: &[transforms],
         push_constant_ranges: &[],
     });
+    build(device, sample_count, &layout, "Render Pipeline", "vs_main", &[vertex_layout()])
+}
+
+/// Create the retained-mesh pipeline: the scene pipeline with a per-instance transform and tint.
+pub fn create_mesh(
+    device: &wgpu::Device,
+    sample_count: u32,
+    transforms: &wgpu::BindGroupLayout,
+) -> wgpu::RenderPipeline {
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Mesh Pipeline Layout"),
+        bind_group_layouts: &[transforms],
+        push_constant_ranges: &[],
+    });
+    let buffers = [vertex_layout(), super::meshes::instance_layout()];
+    build(device, sample_count, &layout, "Mesh Pipeline", "vs_mesh", &buffers)
+}
+
+fn build(
+    device: &wgpu::Device,
+    sample_count: u32,
+    layout: &wgpu::PipelineLayout,
+    label: &str,
+    vertex_entry: &str,
+    buffers: &[wgpu::VertexBufferLayout<'_>],
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Shader"),
+        source: wgpu::ShaderSource::Wgsl(SHADER_SOURCE.into()),
+    });
 
     device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("Render Pipeline"),
-        layout: Some(&layout),
+        label: Some(label),
+        layout: Some(layout),
         vertex: wgpu::VertexState {
             module: &shader,
-            entry_point: Some("vs_main"),
-            buffers: &[vertex_layout()],
+            entry_point: Some(vertex_entry),
+            buffers,
             compilation_options: wgpu::PipelineCompilationOptions::default(),
         },
         fragment: Some(wgpu::FragmentState {
             module: &shader,
             entry_point: Some("fs_main"),
-            targets: &[Some(wgpu::ColorTargetState {
-                format,
-                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                write_mask: wgpu::ColorWrites::ALL,
-            })],
+            targets: &scene_targets(),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
         }),
         primitive: wgpu::PrimitiveState {
@@ -69,7 +183,7 @@ pub fn create(device: &wgpu::Device, format: wgpu::TextureFormat) -> wgpu::Rende
         },
         depth_stencil: None,
         multisample: wgpu::MultisampleState {
-            count: SAMPLE_COUNT,
+            count: sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -78,6 +192,22 @@ pub fn create(device: &wgpu::Device, format: wgpu::TextureFormat) -> wgpu::Rende
     })
 }
 
+/// Scene pass color targets: alpha-blended color and additive glow.
+pub fn scene_targets() -> [Option<wgpu::ColorTargetState>; 2] {
+    [
+        Some(wgpu::ColorTargetState {
+            format: HDR_FORMAT,
+            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+            write_mask: wgpu::ColorWrites::ALL,
+        }),
+        Some(wgpu::ColorTargetState {
+            format: HDR_FORMAT,
+            blend: Some(ADDITIVE_BLENDING),
+            write_mask: wgpu::ColorWrites::ALL,
+        }),
+    ]
+}
+
 fn vertex_layout() -> wgpu::VertexBufferLayout<'static> {
     wgpu::VertexBufferLayout {
         array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
@@ -91,71 +221,135 @@ fn vertex_layout() -> wgpu::VertexBufferLayout<'static> {
             wgpu::VertexAttribute {
                 offset: 8,
                 shader_location: 1,
+                format: wgpu::VertexFormat::Float32x2,
+            },
+            wgpu::VertexAttribute {
+                offset: 16,
+                shader_location: 2,
                 format: wgpu::VertexFormat::Float32x4,
             },
+            wgpu::VertexAttribute {
+                offset: 32,
+                shader_location: 3,
+                format: wgpu::VertexFormat::Float32,
+            },
+            wgpu::VertexAttribute {
+                offset: 36,
+                shader_location: 4,
+                format: wgpu::VertexFormat::Uint32,
+            },
         ],
     }
 }
 
-/// Create MSAA texture for multisampled rendering.
-pub fn create_msaa_texture(
-    device: &wgpu::Device,
-    format: wgpu::TextureFormat,
-    width: u32,
-    height: u32,
-) -> wgpu::TextureView {
-    let texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("MSAA Texture"),
-        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
-        mip_level_count: 1,
-        sample_count: SAMPLE_COUNT,
-        dimension: wgpu::TextureDimension::D2,
-        format,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        view_formats: &[],
-    });
-    texture.create_view(&wgpu::TextureViewDescriptor::default())
+/// The supported sample count closest to `requested` without exceeding it.
+///
+/// `supported` always contains 1, so this falls back to no MSAA at worst.
+pub fn pick_sample_count(requested: u32, supported: &[u32]) -> u32 {
+    supported.iter().copied().filter(|&n| n <= requested).max().unwrap_or(1)
+}
+
+/// Create a wgpu instance limited to `backends`.
+pub fn create_instance(backends: wgpu::Backends) -> wgpu::Instance {
+    wgpu::Instance::new(&wgpu::InstanceDescriptor { backends, ..Default::default() })
 }
 
 /// Initialize wgpu device, queue, and surface configuration.
+///
+/// Presentation options `config` requests but the surface lacks fall back to
+/// supported ones.
 pub async fn init_device(
     instance: &wgpu::Instance,
     surface: &wgpu::Surface<'_>,
     width: u32,
     height: u32,
-) -> Result<(wgpu::Device, wgpu::Queue, wgpu::SurfaceConfiguration), String> {
+    config: &RendererConfig,
+) -> Result<(Gpu, wgpu::SurfaceConfiguration, SurfaceModes), RenderError> {
     let adapter = instance
         .request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::default(),
+            power_preference: config.power_preference,
             compatible_surface: Some(surface),
             force_fallback_adapter: false,
         })
         .await
-        .ok_or("Failed to find suitable GPU adapter")?;
+        .ok_or(RenderError::NoAdapter)?;
 
-    let (device, queue) = adapter
-        .request_device(&wgpu::DeviceDescriptor::default(), None)
-        .await
-        .map_err(|e| e.to_string())?;
+    let gpu = request_device(&adapter).await?;
 
     let caps = surface.get_capabilities(&adapter);
     let format = caps
         .formats
         .iter()
         .find(|f| f.is_srgb())
+        .or(caps.formats.first())
         .copied()
-        .unwrap_or(caps.formats[0]);
+        .ok_or(RenderError::UnsupportedFormat)?;
+    let modes = SurfaceModes::new(&caps);
 
-    let config = wgpu::SurfaceConfiguration {
+    let surface_config = wgpu::SurfaceConfiguration {
         usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
         format,
         width,
         height,
-        present_mode: wgpu::PresentMode::Fifo,
-        alpha_mode: caps.alpha_modes[0],
+        present_mode: modes.present_mode(config.present_mode),
+        alpha_mode: modes.alpha_mode(config.alpha_mode),
         view_formats: vec![],
-        desired_maximum_frame_latency: 2,
+        desired_maximum_frame_latency: config.frame_latency,
+    };
+
+    Ok((gpu, surface_config, modes))
+}
+
+/// Initialize a wgpu device and queue without a surface.
+///
+/// Tries the preferred adapter first and falls back to the software adapter,
+/// so headless rendering works on machines without a GPU.
+pub async fn init_headless_device(
+    instance: &wgpu::Instance,
+    power_preference: wgpu::PowerPreference,
+) -> Result<Gpu, RenderError> {
+    let adapter = match instance.request_adapter(&headless_options(power_preference, false)).await {
+        Some(adapter) => adapter,
+        None => instance
+            .request_adapter(&headless_options(power_preference, true))
+            .await
+            .ok_or(RenderError::NoAdapter)?,
     };
+    request_device(&adapter).await
+}
+
+/// Request a device, enabling adapter-specific format features when available
+/// so MSAA counts beyond WebGPU's guaranteed 1 and 4 can be used, and
+/// timestamp queries for GPU profiling.
+async fn request_device(adapter: &wgpu::Adapter) -> Result<Gpu, RenderError> {
+    let optional = wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES | wgpu::Features::TIMESTAMP_QUERY;
+    let features = adapter.features() & optional;
+    let descriptor = wgpu::DeviceDescriptor { required_features: features, ..Default::default() };
+    let (device, queue) = adapter.request_device(&descriptor, None).await?;
+    let sample_counts = supported_sample_counts(adapter, &device);
+    Ok(Gpu { device, queue, sample_counts })
+}
 
-    Ok((device, queue, config))
+/// MSAA sample counts `HDR_FORMAT` supports on `device`.
+pub fn supported_sample_counts(adapter: &wgpu::Adapter, device: &wgpu::Device) -> Vec<u32> {
+    if device.features().contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+        let flags = adapter.get_texture_format_features(HDR_FORMAT).flags;
+        if !flags.contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE) {
+            return vec![1];
+        }
+        flags.supported_sample_counts()
+    } else {
+        WEBGPU_SAMPLE_COUNTS.to_vec()
+    }
+}
+
+fn headless_options(
+    power_preference: wgpu::PowerPreference,
+    force_fallback_adapter: bool,
+) -> wgpu::RequestAdapterOptions<'static, 'static> {
+    wgpu::RequestAdapterOptions {
+        power_preference,
+        compatible_surface: None,
+        force_fallback_adapter,
+    }
 }
@@ -0,0 +1,230 @@
+//! GPU pass timing with timestamp queries.
+//!
+//! Each profiled frame writes a begin and end timestamp around every render
+//! pass, resolves them into a buffer and copies that into one of a few
+//! readback buffers. The readback is mapped asynchronously and collected on a
+//! later frame, so profiling never stalls the CPU on the GPU. Frames that find
+//! every readback buffer still in flight are not profiled.
+
+use super::WgpuRenderer;
+use std::sync::mpsc::{Receiver, TryRecvError, channel};
+
+/// Readback buffers in flight; timings lag the current frame by up to this many frames.
+const SLOTS: usize = 3;
+
+/// Render passes that are timed, in encoding order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpuPass {
+    /// Strokes and retained meshes into the HDR scene targets.
+    Scene,
+    /// Phosphor persistence accumulation.
+    Persistence,
+    /// All blur passes of the glow chain.
+    Glow,
+    /// Composite onto the render target.
+    Composite,
+}
+
+impl GpuPass {
+    const COUNT: usize = 4;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// GPU time of each render pass of one frame, in milliseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GpuTimings {
+    /// Scene pass drawing strokes and retained meshes.
+    pub scene_ms: f32,
+    /// Persistence pass; `None` when persistence is off.
+    pub persistence_ms: Option<f32>,
+    /// Glow blur passes; `None` when the glow intensity is 0.
+    pub glow_ms: Option<f32>,
+    /// Composite pass.
+    pub composite_ms: f32,
+}
+
+impl GpuTimings {
+    /// Sum of all pass times.
+    #[must_use]
+    pub fn total_ms(&self) -> f32 {
+        self.scene_ms + self.persistence_ms.unwrap_or(0.0) + self.glow_ms.unwrap_or(0.0) + self.composite_ms
+    }
+}
+
+impl WgpuRenderer {
+    /// Whether the device supports GPU timestamp queries.
+    #[must_use]
+    pub fn gpu_profiling_supported(&self) -> bool {
+        self.device.features().contains(wgpu::Features::TIMESTAMP_QUERY)
+    }
+
+    /// Time each render pass on the GPU with timestamp queries.
+    ///
+    /// Returns whether profiling is now on; without `TIMESTAMP_QUERY` support
+    /// it stays off and rendering is unaffected.
+    pub fn set_gpu_profiling(&mut self, enabled: bool) -> bool {
+        if !enabled {
+            self.profiler = None;
+        } else if self.profiler.is_none() {
+            self.profiler = GpuProfiler::new(&self.device, &self.queue);
+        }
+        self.profiler.is_some()
+    }
+
+    /// Per-pass GPU times of the most recent profiled frame whose results have arrived.
+    ///
+    /// Results are read back asynchronously and lag rendering by a few
+    /// frames. `None` until the first results arrive or when profiling is off.
+    #[must_use]
+    pub fn gpu_timings(&self) -> Option<GpuTimings> {
+        self.profiler.as_ref().and_then(GpuProfiler::latest)
+    }
+}
+
+/// The passes a slot's frame recorded and its pending map.
+struct Pending {
+    frame: u64,
+    passes: [bool; GpuPass::COUNT],
+    mapped: Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+struct Slot {
+    readback: wgpu::Buffer,
+    pending: Option<Pending>,
+}
+
+/// Timestamp queries, resolve buffer and readback ring.
+pub struct GpuProfiler {
+    queries: wgpu::QuerySet,
+    resolve: wgpu::Buffer,
+    slots: Vec<Slot>,
+    /// Slot and passes of the frame being encoded.
+    recording: Option<(usize, [bool; GpuPass::COUNT])>,
+    period_ns: f32,
+    /// Profiled frames so far, to order results arriving from several slots.
+    frames: u64,
+    latest: Option<(u64, GpuTimings)>,
+}
+
+impl GpuProfiler {
+    /// Bytes per pass in the resolve buffer; resolve offsets must be 256-byte aligned.
+    const PASS_STRIDE: u64 = wgpu::QUERY_RESOLVE_BUFFER_ALIGNMENT;
+
+    /// Create a profiler, or `None` if `device` lacks `TIMESTAMP_QUERY`.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let queries = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Pass Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: 2 * GpuPass::COUNT as u32,
+        });
+        let size = Self::PASS_STRIDE * GpuPass::COUNT as u64;
+        let buffer = |label, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor { label: Some(label), size, usage, mapped_at_creation: false })
+        };
+        let resolve = buffer("Timestamp Resolve", wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC);
+        let slots = (0..SLOTS)
+            .map(|_| Slot {
+                readback: buffer("Timestamp Readback", wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ),
+                pending: None,
+            })
+            .collect();
+        let period_ns = queue.get_timestamp_period();
+        Some(Self { queries, resolve, slots, recording: None, period_ns, frames: 0, latest: None })
+    }
+
+    /// Timings of the most recent frame whose results have arrived.
+    pub fn latest(&self) -> Option<GpuTimings> {
+        self.latest.map(|(_, timings)| timings)
+    }
+
+    /// Collect finished readbacks and start profiling a frame with the given
+    /// optional passes, unless every readback buffer is still in flight.
+    pub fn begin_frame(&mut self, device: &wgpu::Device, persistence: bool, glow: bool) {
+        device.poll(wgpu::Maintain::Poll);
+        self.collect();
+        let free = self.slots.iter().position(|slot| slot.pending.is_none());
+        self.recording = free.map(|slot| (slot, [true, persistence, glow, true]));
+    }
+
+    /// Timestamp writes for `pass` in the frame being profiled, if any.
+    pub fn writes(&self, pass: GpuPass) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        self.recording.map(|_| {
+            let begin = 2 * pass.index() as u32;
+            wgpu::RenderPassTimestampWrites {
+                query_set: &self.queries,
+                beginning_of_pass_write_index: Some(begin),
+                end_of_pass_write_index: Some(begin + 1),
+            }
+        })
+    }
+
+    /// Resolve the frame's timestamps into its readback buffer.
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        let Some((slot, passes)) = self.recording else { return };
+        for i in (0..GpuPass::COUNT).filter(|&i| passes[i]) {
+            let queries = 2 * i as u32..2 * i as u32 + 2;
+            encoder.resolve_query_set(&self.queries, queries, &self.resolve, Self::PASS_STRIDE * i as u64);
+        }
+        let size = self.resolve.size();
+        encoder.copy_buffer_to_buffer(&self.resolve, 0, &self.slots[slot].readback, 0, size);
+    }
+
+    /// Start mapping the frame's readback buffer after its commands were submitted.
+    pub fn end_frame(&mut self) {
+        let Some((slot, passes)) = self.recording.take() else { return };
+        let (tx, mapped) = channel();
+        let slot = &mut self.slots[slot];
+        slot.readback.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.frames += 1;
+        slot.pending = Some(Pending { frame: self.frames, passes, mapped });
+    }
+
+    /// Read every mapped readback buffer, keeping the newest timings.
+    fn collect(&mut self) {
+        for slot in &mut self.slots {
+            let Some(pending) = &slot.pending else { continue };
+            let result = match pending.mapped.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => continue,
+                Err(TryRecvError::Disconnected) => Err(wgpu::BufferAsyncError),
+            };
+            let (frame, passes) = (pending.frame, pending.passes);
+            slot.pending = None;
+            if result.is_ok() {
+                let timings = read_timings(&slot.readback, passes, self.period_ns);
+                slot.readback.unmap();
+                if self.latest.is_none_or(|(latest, _)| latest < frame) {
+                    self.latest = Some((frame, timings));
+                }
+            }
+        }
+    }
+}
+
+fn read_timings(readback: &wgpu::Buffer, passes: [bool; GpuPass::COUNT], period_ns: f32) -> GpuTimings {
+    let data = readback.slice(..).get_mapped_range();
+    let pass_ms = |pass: GpuPass| {
+        let i = pass.index();
+        if !passes[i] {
+            return None;
+        }
+        let offset = GpuProfiler::PASS_STRIDE as usize * i;
+        let tick = |at: usize| u64::from_ne_bytes(data[at..at + 8].try_into().expect("8 bytes"));
+        let ticks = tick(offset + 8).saturating_sub(tick(offset));
+        Some(ticks as f32 * period_ns / 1_000_000.0)
+    };
+    GpuTimings {
+        scene_ms: pass_ms(GpuPass::Scene).unwrap_or(0.0),
+        persistence_ms: pass_ms(GpuPass::Persistence),
+        glow_ms: pass_ms(GpuPass::Glow),
+        composite_ms: pass_ms(GpuPass::Composite).unwrap_or(0.0),
+    }
+}
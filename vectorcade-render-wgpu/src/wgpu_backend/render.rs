@@ -0,0 +1,172 @@
+//! Per-frame rendering: tessellate, upload, draw and present or record into a caller's encoder.
+
+use super::composite::{CompositeFrame, LoadMode};
+use super::glow::GlowPass;
+use super::profiler::{GpuPass, GpuProfiler};
+use super::{RenderError, WgpuRenderer};
+use crate::commands::Batch;
+use crate::{RenderStats, VectorRenderer};
+use vectorcade_shared::draw::DrawCmd;
+
+impl VectorRenderer for WgpuRenderer {
+    fn render(&mut self, cmds: &[DrawCmd]) -> RenderStats {
+        let mut stats = RenderStats::default();
+        // Surface errors are counted in the stats; see `try_render` to handle them.
+        let _ = self.render_frame(cmds, &mut stats);
+        stats
+    }
+}
+
+impl WgpuRenderer {
+    /// Render a frame like [`VectorRenderer::render`], reporting why it could not be shown.
+    ///
+    /// Lost and outdated surfaces are reconfigured and acquired again, and a
+    /// frame whose surface texture times out is skipped with nothing drawn.
+    /// Each case is counted in the returned stats. `render` ignores the
+    /// errors below; this returns them so the caller can react, e.g. by
+    /// recreating the renderer after [`RenderError::OutOfMemory`].
+    ///
+    /// # Errors
+    /// Returns `OutOfMemory`, a surface error that persisted after
+    /// reconfiguring, or any other acquisition failure. Headless renderers
+    /// never fail.
+    pub fn try_render(&mut self, cmds: &[DrawCmd]) -> Result<RenderStats, RenderError> {
+        let mut stats = RenderStats::default();
+        self.render_frame(cmds, &mut stats)?;
+        Ok(stats)
+    }
+
+    /// Record a frame of `cmds` into the caller's `encoder`, compositing onto `view`.
+    ///
+    /// Tessellates and uploads like [`VectorRenderer::render`], but neither
+    /// acquires nor presents a surface texture and does not submit: the frame
+    /// takes effect when the caller submits `encoder`. `view` must have the
+    /// format of the renderer's target (see [`from_device`](Self::from_device)
+    /// to choose it); its size should match [`size`](Self::size), as stroke
+    /// widths and the [viewport rect](Self::viewport_rect) are in target
+    /// pixels. `load` chooses whether the view is cleared or the frame is
+    /// blended over its contents. GPU profiling skips these frames, as
+    /// timestamps are read back after a submission the renderer does not
+    /// control.
+    ///
+    /// Record at most one `render_to` per encoder: uploads go through the
+    /// queue and the scene targets are shared, so every call in an encoder
+    /// draws the data of the last one. Submit in between to draw several
+    /// frames.
+    pub fn render_to(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        cmds: &[DrawCmd],
+        load: LoadMode,
+    ) -> RenderStats {
+        let mut stats = self.prepare_frame(cmds);
+        self.upload_frame(&mut stats);
+        stats.draw_calls = self.encode_frame(encoder, view, load, None);
+        stats
+    }
+
+    /// Tessellate, upload, draw and present `cmds`, filling in `stats` as far as it gets.
+    fn render_frame(&mut self, cmds: &[DrawCmd], stats: &mut RenderStats) -> Result<(), RenderError> {
+        *stats = self.prepare_frame(cmds);
+        let Some(frame) = self.target.acquire(&self.device, stats)? else {
+            return Ok(());
+        };
+        self.upload_frame(stats);
+        if let Some(profiler) = &mut self.profiler {
+            profiler.begin_frame(&self.device, self.persistence.is_some(), self.glow_intensity > 0.0);
+        }
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        stats.draw_calls = self.encode_frame(&mut encoder, &frame.view, LoadMode::Clear, self.profiler.as_ref());
+        if let Some(profiler) = &self.profiler {
+            profiler.resolve(&mut encoder);
+        }
+        self.queue.submit(std::iter::once(encoder.finish()));
+        if let Some(profiler) = &mut self.profiler {
+            profiler.end_frame();
+        }
+        frame.present();
+        Ok(())
+    }
+
+    /// Tessellate `cmds` and upload queued retained-mesh instances.
+    fn prepare_frame(&mut self, cmds: &[DrawCmd]) -> RenderStats {
+        self.commands.state.set_projection(self.projection());
+        let mut stats = self.commands.tessellate(cmds, self.px_to_ndc());
+        self.meshes.prepare(&self.device, &self.queue, &mut stats);
+        stats
+    }
+
+    /// Upload the tessellated geometry, transforms and line instances.
+    fn upload_frame(&mut self, stats: &mut RenderStats) {
+        let geometry = &self.commands.geometry;
+        self.buffers.update(&self.device, &self.queue, &geometry.vertices, &geometry.indices, stats);
+        self.transforms.update(&self.device, &self.queue, &geometry.transforms, stats);
+        self.buffers.update_instances(&self.device, &self.queue, &self.commands.lines, stats);
+        let (width, height) = self.scene_size();
+        self.transforms.set_offset_scale(&self.queue, width, height, geometry.projection_scale);
+        self.lines.set_viewport(&self.queue, width, height);
+    }
+
+    /// Record every pass of the frame into `encoder`, returning the number of draw calls.
+    ///
+    /// Passes write timestamps when `profiler` is given.
+    fn encode_frame(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        load: LoadMode,
+        profiler: Option<&GpuProfiler>,
+    ) -> u32 {
+        let timestamps = |pass| profiler.and_then(|p| p.writes(pass));
+        let mut draw_calls = self.draw_scene(encoder, timestamps(GpuPass::Scene));
+        if let Some(persistence) = &self.persistence {
+            persistence.encode(&self.queue, encoder, timestamps(GpuPass::Persistence));
+            draw_calls += 1;
+        }
+        if self.glow_intensity > 0.0 {
+            self.glow.encode(encoder, timestamps(GpuPass::Glow));
+            draw_calls += GlowPass::DRAW_CALLS;
+        }
+        let frame = CompositeFrame {
+            background: self.commands.state.background(),
+            glow_intensity: self.glow_intensity,
+            rect: self.viewport_uv(),
+            bars: self.viewport.bars(),
+        };
+        self.composite.encode(&self.queue, encoder, view, frame, load, timestamps(GpuPass::Composite));
+        draw_calls + 1
+    }
+
+    /// Render strokes into the HDR scene and glow targets, batch by batch.
+    ///
+    /// Returns the number of draw calls.
+    fn draw_scene(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites<'_>>,
+    ) -> u32 {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Scene Pass"),
+            color_attachments: &self.scene.attachments(),
+            timestamp_writes,
+            ..Default::default()
+        });
+
+        let mut draw_calls = self.meshes.draw(&mut pass, &self.mesh_pipeline, self.transforms.group());
+        for batch in &self.commands.batches {
+            match batch {
+                Batch::Mesh(indices) => {
+                    pass.set_pipeline(&self.pipeline);
+                    pass.set_bind_group(0, self.transforms.group(), &[]);
+                    pass.set_vertex_buffer(0, self.buffers.vertex.slice(..));
+                    pass.set_index_buffer(self.buffers.index.slice(..), wgpu::IndexFormat::Uint32);
+                    pass.draw_indexed(indices.clone(), 0, 0..1);
+                }
+                Batch::Lines(instances) => self.lines.draw(&mut pass, &self.buffers.instance, instances.clone()),
+            }
+            draw_calls += 1;
+        }
+        draw_calls
+    }
+}
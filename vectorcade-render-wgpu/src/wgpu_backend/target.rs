@@ -0,0 +1,148 @@
+//! Final render target: a window surface or an owned offscreen texture.
+
+use super::RenderError;
+use super::config::SurfaceModes;
+use crate::RenderStats;
+
+/// Format of the texture owned by headless renderers.
+pub const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Where the composite pass writes each frame.
+pub enum RenderTarget {
+    /// A configured window or canvas surface, presented after each frame.
+    Surface {
+        surface: wgpu::Surface<'static>,
+        config: wgpu::SurfaceConfiguration,
+        modes: SurfaceModes,
+    },
+    /// An owned texture, kept after each frame so it can be read back.
+    Texture(wgpu::Texture),
+}
+
+/// A view to render one frame into, plus the surface texture to present.
+pub struct Frame {
+    output: Option<wgpu::SurfaceTexture>,
+    pub view: wgpu::TextureView,
+}
+
+impl RenderTarget {
+    /// Create an owned offscreen texture target.
+    pub fn texture(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> Self {
+        Self::Texture(create_texture(device, width, height, format))
+    }
+
+    /// Format the composite pass writes.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match self {
+            Self::Surface { config, .. } => config.format,
+            Self::Texture(texture) => texture.format(),
+        }
+    }
+
+    /// Current size in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        match self {
+            Self::Surface { config, .. } => (config.width, config.height),
+            Self::Texture(texture) => (texture.width(), texture.height()),
+        }
+    }
+
+    /// Reconfigure the surface or recreate the texture at a new size.
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        match self {
+            Self::Surface { surface, config, .. } => {
+                config.width = width;
+                config.height = height;
+                surface.configure(device, config);
+            }
+            Self::Texture(texture) => *texture = create_texture(device, width, height, texture.format()),
+        }
+    }
+
+    /// The surface configuration, or `None` for texture targets.
+    pub fn surface_config(&self) -> Option<&wgpu::SurfaceConfiguration> {
+        match self {
+            Self::Surface { config, .. } => Some(config),
+            Self::Texture(_) => None,
+        }
+    }
+
+    /// Change the surface configuration and apply it; a no-op for texture targets.
+    pub fn reconfigure(
+        &mut self,
+        device: &wgpu::Device,
+        update: impl FnOnce(&mut wgpu::SurfaceConfiguration, &SurfaceModes),
+    ) {
+        if let Self::Surface { surface, config, modes } = self {
+            update(config, modes);
+            surface.configure(device, config);
+        }
+    }
+
+    /// Acquire the view for the next frame, or `None` to skip a frame that timed out.
+    ///
+    /// A lost or outdated surface is reconfigured with the current `config`
+    /// and acquired once more. Every surface error is counted in `stats`.
+    pub fn acquire(&self, device: &wgpu::Device, stats: &mut RenderStats) -> Result<Option<Frame>, RenderError> {
+        let (surface, config) = match self {
+            Self::Surface { surface, config, .. } => (surface, config),
+            Self::Texture(texture) => {
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                return Ok(Some(Frame { output: None, view }));
+            }
+        };
+        let mut reconfigured = false;
+        loop {
+            let error = match surface.get_current_texture() {
+                Ok(output) => {
+                    let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+                    return Ok(Some(Frame { output: Some(output), view }));
+                }
+                Err(error) => error,
+            };
+            count_error(stats, &error);
+            match error {
+                wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated if !reconfigured => {
+                    surface.configure(device, config);
+                    reconfigured = true;
+                }
+                wgpu::SurfaceError::Timeout => return Ok(None),
+                error => return Err(error.into()),
+            }
+        }
+    }
+}
+
+impl Frame {
+    /// Present the surface texture; a no-op for texture targets.
+    pub fn present(self) {
+        if let Some(output) = self.output {
+            output.present();
+        }
+    }
+}
+
+fn count_error(stats: &mut RenderStats, error: &wgpu::SurfaceError) {
+    match error {
+        wgpu::SurfaceError::Outdated => stats.surface_outdated += 1,
+        wgpu::SurfaceError::Lost => stats.surface_lost += 1,
+        wgpu::SurfaceError::Timeout => stats.surface_timeouts += 1,
+        wgpu::SurfaceError::OutOfMemory => stats.surface_out_of_memory += 1,
+        wgpu::SurfaceError::Other => {}
+    }
+}
+
+fn create_texture(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen Target"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
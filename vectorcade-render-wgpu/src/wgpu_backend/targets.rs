@@ -0,0 +1,103 @@
+//! Offscreen HDR targets the scene pass renders into.
+
+use super::pipeline::HDR_FORMAT;
+
+/// Scene and glow attachments: multisampled textures plus their single-sample
+/// resolves, or just the single-sample textures when MSAA is off.
+pub struct SceneTargets {
+    msaa: Option<[wgpu::TextureView; 2]>,
+    pub color: wgpu::TextureView,
+    pub glow: wgpu::TextureView,
+}
+
+impl SceneTargets {
+    /// Create scene targets for the given size and MSAA sample count.
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, sample_count: u32) -> Self {
+        let msaa = wgpu::TextureUsages::RENDER_ATTACHMENT;
+        let resolve = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+        Self {
+            msaa: (sample_count > 1).then(|| {
+                [
+                    create_view(device, "Scene MSAA", width, height, sample_count, msaa),
+                    create_view(device, "Glow MSAA", width, height, sample_count, msaa),
+                ]
+            }),
+            color: create_view(device, "Scene", width, height, 1, resolve),
+            glow: create_view(device, "Glow Source", width, height, 1, resolve),
+        }
+    }
+
+    /// Color attachments for the scene pass, cleared to transparent.
+    ///
+    /// Multisampled targets are resolved into `color`/`glow`; without MSAA the
+    /// pass writes them directly.
+    pub fn attachments(&self) -> [Option<wgpu::RenderPassColorAttachment<'_>>; 2] {
+        match &self.msaa {
+            Some([color_msaa, glow_msaa]) => [
+                resolve_attachment(color_msaa, &self.color),
+                resolve_attachment(glow_msaa, &self.glow),
+            ],
+            None => [clear_attachment(&self.color), clear_attachment(&self.glow)],
+        }
+    }
+}
+
+fn resolve_attachment<'a>(
+    msaa: &'a wgpu::TextureView,
+    resolve: &'a wgpu::TextureView,
+) -> Option<wgpu::RenderPassColorAttachment<'a>> {
+    Some(wgpu::RenderPassColorAttachment {
+        view: msaa,
+        resolve_target: Some(resolve),
+        ops: wgpu::Operations {
+            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+            store: wgpu::StoreOp::Discard,
+        },
+    })
+}
+
+/// Single-sample color attachment that is cleared to transparent and stored.
+pub fn clear_attachment(view: &wgpu::TextureView) -> Option<wgpu::RenderPassColorAttachment<'_>> {
+    Some(wgpu::RenderPassColorAttachment {
+        view,
+        resolve_target: None,
+        ops: wgpu::Operations {
+            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+            store: wgpu::StoreOp::Store,
+        },
+    })
+}
+
+/// Create an HDR texture and return its default view.
+pub fn create_view(
+    device: &wgpu::Device,
+    label: &str,
+    width: u32,
+    height: u32,
+    sample_count: u32,
+    usage: wgpu::TextureUsages,
+) -> wgpu::TextureView {
+    create_texture(device, label, width, height, sample_count, usage)
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// Create an HDR texture.
+pub fn create_texture(
+    device: &wgpu::Device,
+    label: &str,
+    width: u32,
+    height: u32,
+    sample_count: u32,
+    usage: wgpu::TextureUsages,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: HDR_FORMAT,
+        usage,
+        view_formats: &[],
+    })
+}
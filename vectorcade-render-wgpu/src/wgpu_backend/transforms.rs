@@ -0,0 +1,110 @@
+//! Storage buffer of the frame's transforms, indexed by `Vertex::transform`,
+//! and the uniform that keeps stroke offsets square in pixels.
+
+use super::buffers;
+use crate::RenderStats;
+use glam::{Mat3, Vec2};
+use wgpu::util::DeviceExt;
+
+/// A transform as laid out in WGSL `array<mat3x3<f32>>`: columns padded to 16 bytes.
+type GpuMat3 = [[f32; 4]; 3];
+
+/// Read-only storage buffer of transforms bound to the scene vertex shader.
+pub struct TransformBuffer {
+    layout: wgpu::BindGroupLayout,
+    /// `vec2` scaling stroke offsets, which are in units of `px_to_ndc`, to
+    /// clip space, then a `vec2` dividing the projection out of transforms.
+    offset_scale: wgpu::Buffer,
+    buffer: wgpu::Buffer,
+    group: wgpu::BindGroup,
+    capacity: usize,
+    staging: Vec<GpuMat3>,
+}
+
+impl TransformBuffer {
+    const INITIAL_TRANSFORMS: usize = 256;
+
+    /// Create the bind group layout and an initial buffer.
+    pub fn new(device: &wgpu::Device) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Transform Layout"),
+            entries: &[
+                buffer_entry(0, wgpu::BufferBindingType::Storage { read_only: true }),
+                buffer_entry(1, wgpu::BufferBindingType::Uniform),
+            ],
+        });
+        let offset_scale = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Offset Scale"),
+            contents: bytemuck::cast_slice(&[1.0f32; 4]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let (buffer, group) = create_buffer(device, &layout, &offset_scale, Self::INITIAL_TRANSFORMS);
+        Self { layout, offset_scale, buffer, group, capacity: Self::INITIAL_TRANSFORMS, staging: Vec::new() }
+    }
+
+    /// Match stroke offsets to a scene of `width`x`height` pixels and a
+    /// projection scaling x and y by `projection_scale`.
+    ///
+    /// Offsets are tessellated in units of `2 / height`, so x is scaled by
+    /// `height / width` to keep strokes as wide as they are tall.
+    pub fn set_offset_scale(&self, queue: &wgpu::Queue, width: u32, height: u32, projection_scale: Vec2) {
+        let unproject = projection_scale.abs().recip();
+        let data = [height as f32 / width as f32, 1.0, unproject.x, unproject.y];
+        queue.write_buffer(&self.offset_scale, 0, bytemuck::cast_slice(&data));
+    }
+
+    /// Layout of the bind group at group 0 of the scene and mesh pipelines.
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    /// Bind group to set before drawing tessellated geometry or retained meshes.
+    pub fn group(&self) -> &wgpu::BindGroup {
+        &self.group
+    }
+
+    /// Upload `transforms`, growing the buffer if needed.
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, transforms: &[Mat3], stats: &mut RenderStats) {
+        if transforms.len() > self.capacity {
+            self.capacity = transforms.len().next_power_of_two();
+            (self.buffer, self.group) = create_buffer(device, &self.layout, &self.offset_scale, self.capacity);
+            stats.buffer_reallocations += 1;
+        }
+        self.staging.clear();
+        let columns = |t: &Mat3| [t.x_axis, t.y_axis, t.z_axis].map(|c| c.extend(0.0).to_array());
+        self.staging.extend(transforms.iter().map(columns));
+        buffers::write(queue, &self.buffer, bytemuck::cast_slice(&self.staging), stats);
+    }
+}
+
+fn buffer_entry(binding: u32, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::VERTEX,
+        ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset: false, min_binding_size: None },
+        count: None,
+    }
+}
+
+fn create_buffer(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    offset_scale: &wgpu::Buffer,
+    capacity: usize,
+) -> (wgpu::Buffer, wgpu::BindGroup) {
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Transform Buffer"),
+        size: (capacity * std::mem::size_of::<GpuMat3>()) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Transform Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 1, resource: offset_scale.as_entire_binding() },
+        ],
+    });
+    (buffer, group)
+}
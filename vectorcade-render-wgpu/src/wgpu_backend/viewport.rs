@@ -0,0 +1,134 @@
+//! Mapping the game's world onto the render target.
+//!
+//! The coordinate system projects display-list points onto the viewport
+//! rect. The scene is rendered into targets the size of that rect, so
+//! strokes keep their pixel widths, and the composite pass places that image
+//! in the rect and fills the rest of the target with the bar colour.
+
+use super::WgpuRenderer;
+use crate::CoordinateSystem;
+use glam::Mat3;
+use vectorcade_shared::Rgba;
+
+/// How the game's normalized [-1, 1] space is mapped onto the render target.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum ViewportMode {
+    /// Cover the whole target, distorting the game's aspect ratio.
+    #[default]
+    Stretch,
+    /// Fit the largest rect of `aspect` (width / height) inside the target,
+    /// with letterbox or pillarbox bars of colour `bars`.
+    Fit { aspect: f32, bars: Rgba },
+    /// Cover the target with a rect of `aspect`, cropping what overflows.
+    Fill { aspect: f32 },
+}
+
+/// Where the game's square lands on the target, in target pixels.
+///
+/// Offsets are negative when `Fill` crops the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ViewportRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ViewportMode {
+    /// The rect the game's square maps to in a `width`x`height` target.
+    pub fn rect(self, width: u32, height: u32) -> ViewportRect {
+        let whole = ViewportRect { x: 0, y: 0, width, height };
+        let (aspect, fit) = match self {
+            Self::Fit { aspect, .. } => (aspect, true),
+            Self::Fill { aspect } => (aspect, false),
+            Self::Stretch => return whole,
+        };
+        if !(aspect.is_finite() && aspect > 0.0) {
+            return whole;
+        }
+        let (w, h) = (width as f32, height as f32);
+        // Fit a game wider than the target, or fill with a narrower one, to the target width.
+        let (rect_w, rect_h) = if (aspect > w / h) == fit { (w, w / aspect) } else { (h * aspect, h) };
+        let (rect_w, rect_h) = (rect_w.round().max(1.0) as u32, rect_h.round().max(1.0) as u32);
+        let centered = |outer: u32, inner: u32| (i64::from(outer) - i64::from(inner)) as i32 / 2;
+        ViewportRect { x: centered(width, rect_w), y: centered(height, rect_h), width: rect_w, height: rect_h }
+    }
+
+    /// Colour of the bars beside the rect; transparent when there are none.
+    pub fn bars(self) -> [f32; 4] {
+        match self {
+            Self::Fit { bars, .. } => [bars.0, bars.1, bars.2, bars.3],
+            Self::Stretch | Self::Fill { .. } => [0.0; 4],
+        }
+    }
+}
+
+impl WgpuRenderer {
+    /// Map the game's [-1, 1] square onto the target with `mode`.
+    ///
+    /// `Fit` and `Fill` keep the game's declared aspect ratio; stroke widths
+    /// stay in target pixels in every mode. Persistence trails are reset
+    /// when the rect changes size.
+    pub fn set_viewport_mode(&mut self, mode: ViewportMode) {
+        let before = self.scene_size();
+        self.viewport = mode;
+        if self.scene_size() != before {
+            self.update_meshes();
+            self.recreate_scene();
+        }
+    }
+
+    /// Current viewport mapping.
+    #[must_use]
+    pub fn viewport_mode(&self) -> ViewportMode {
+        self.viewport
+    }
+
+    /// Where the game's square lands on the target, e.g. to map pointer input.
+    #[must_use]
+    pub fn viewport_rect(&self) -> ViewportRect {
+        let (width, height) = self.target.size();
+        self.viewport.rect(width, height)
+    }
+
+    /// Interpret display-list points and `draw_mesh` transforms in `coords`.
+    ///
+    /// `Pixels` are pixels of the [viewport rect](Self::viewport_rect).
+    /// Stroke widths and text sizes stay in pixels in every convention.
+    pub fn set_coordinate_system(&mut self, coords: CoordinateSystem) {
+        self.coords = coords;
+        self.update_meshes();
+    }
+
+    /// Current coordinate convention of display lists.
+    #[must_use]
+    pub fn coordinate_system(&self) -> CoordinateSystem {
+        self.coords
+    }
+
+    /// World-to-NDC projection for the current scene size.
+    pub(super) fn projection(&self) -> Mat3 {
+        let (width, height) = self.scene_size();
+        self.coords.projection(width, height)
+    }
+
+    /// Re-tessellate retained meshes if the pixel size or projection changed.
+    pub(super) fn update_meshes(&mut self) {
+        self.meshes.set_projection(&self.device, self.px_to_ndc(), self.projection());
+    }
+
+    /// Size of the scene targets: the viewport rect, within the device's texture limit.
+    pub(super) fn scene_size(&self) -> (u32, u32) {
+        let rect = self.viewport_rect();
+        let max = self.device.limits().max_texture_dimension_2d;
+        (rect.width.min(max), rect.height.min(max))
+    }
+
+    /// The viewport rect in target UV coordinates: origin, then size.
+    pub(super) fn viewport_uv(&self) -> [f32; 4] {
+        let (width, height) = self.target.size();
+        let (w, h) = (width as f32, height as f32);
+        let rect = self.viewport_rect();
+        [rect.x as f32 / w, rect.y as f32 / h, rect.width as f32 / w, rect.height as f32 / h]
+    }
+}
@@ -0,0 +1,124 @@
+//! Checks that a warmed-up frame performs no heap allocations.
+
+use glam::{Mat3, Vec2};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use vectorcade_render_wgpu::{SoftwareRenderer, VectorRenderer};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+
+/// Counts allocations made by threads that enabled counting.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn count() {
+    if COUNTING.try_with(Cell::get).unwrap_or(false) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Allocations made by `f` on this thread.
+fn allocations_in(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    COUNTING.with(|c| c.set(true));
+    f();
+    COUNTING.with(|c| c.set(false));
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// An Asteroids-style frame: rocks under transforms, bullets and a border.
+fn frame(t: f32) -> Vec<DrawCmd> {
+    let stroke = || Stroke::new(Rgba::WHITE, 2.0);
+    let rock = vec![
+        Vec2::new(0.0, 0.1),
+        Vec2::new(0.08, 0.04),
+        Vec2::new(0.06, -0.08),
+        Vec2::new(-0.05, -0.09),
+        Vec2::new(-0.09, 0.02),
+    ];
+    let mut cmds = vec![DrawCmd::Clear { color: Rgba::BLACK }];
+    for i in 0..8 {
+        let center = Vec2::new(i as f32 * 0.2 - 0.7, (t + i as f32).sin() * 0.5);
+        cmds.push(DrawCmd::PushTransform(Mat3::from_scale_angle_translation(Vec2::ONE, t * 0.3, center)));
+        cmds.push(DrawCmd::Polyline { pts: rock.clone(), closed: true, stroke: stroke() });
+        cmds.push(DrawCmd::PopTransform);
+    }
+    for i in 0..20 {
+        let a = Vec2::new(i as f32 * 0.09 - 0.9, t.cos() * 0.3);
+        cmds.push(DrawCmd::Line(Line2 { a, b: a + Vec2::new(0.01, 0.0), stroke: stroke() }));
+    }
+    let border = vec![Vec2::new(-0.95, -0.95), Vec2::new(0.95, -0.95), Vec2::new(0.95, 0.95), Vec2::new(-0.95, 0.95)];
+    cmds.push(DrawCmd::Polyline { pts: border, closed: true, stroke: stroke() });
+    cmds
+}
+
+#[test]
+fn steady_state_frames_do_not_allocate() {
+    let mut r = SoftwareRenderer::new(96, 64);
+    let frames: Vec<Vec<DrawCmd>> = (0..4).map(|i| frame(i as f32 * 0.1)).collect();
+    r.render(&frames[0]);
+    r.render(&frames[1]);
+
+    for cmds in &frames[2..] {
+        let allocations = allocations_in(|| {
+            let _ = r.render(cmds);
+        });
+        assert_eq!(allocations, 0);
+    }
+}
+
+/// Text is the exception: `VectorFont::glyph_paths` returns owned paths, so
+/// every glyph allocates upstream. The rest of a text frame still does not.
+#[test]
+#[cfg_attr(not(feature = "fonts"), ignore = "text needs the fonts feature")]
+fn text_allocates_only_for_glyph_paths() {
+    let with_text = |text: &str| {
+        let mut cmds = frame(0.0);
+        cmds.push(DrawCmd::Text {
+            pos: Vec2::new(-0.5, 0.8),
+            text: text.to_string(),
+            size_px: 12.0,
+            color: Rgba::WHITE,
+            style: vectorcade_shared::font::FontStyleId::ATARI,
+        });
+        cmds
+    };
+    let (short, long) = (with_text("AB"), with_text("ABAB"));
+    let mut r = SoftwareRenderer::new(96, 64);
+    r.render(&long);
+    r.render(&long);
+
+    let per_frame = |r: &mut SoftwareRenderer, cmds: &[DrawCmd]| {
+        allocations_in(|| {
+            let _ = r.render(cmds);
+        })
+    };
+    let two_glyphs = per_frame(&mut r, &short);
+    assert!(two_glyphs > 0, "glyph_paths allocates");
+    assert_eq!(per_frame(&mut r, &long), 2 * two_glyphs, "allocations scale with glyphs only");
+}
@@ -0,0 +1,75 @@
+//! End-to-end tests for the `vectorcade-render` command-line tool.
+
+#![cfg(feature = "cli")]
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+use vectorcade_render_wgpu::{NullRenderer, RecordingRenderer};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::DrawCmd;
+
+const JSON_FRAME: &str = r#"[
+    { "Clear": { "color": [0, 0, 1, 1] } },
+    { "Line": { "a": [-0.5, 0], "b": [0.5, 0], "stroke": { "color": [1, 1, 1, 1], "width_px": 3 } } },
+    "PopTransform"
+]"#;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vectorcade-render-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create scratch dir");
+    dir
+}
+
+fn cli() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_vectorcade-render"))
+}
+
+fn load(path: &std::path::Path) -> (u32, u32) {
+    let decoder = png::Decoder::new(std::fs::File::open(path).expect("png exists"));
+    let reader = decoder.read_info().expect("valid png");
+    (reader.info().width, reader.info().height)
+}
+
+#[test]
+fn renders_json_frame_with_software_backend() {
+    let dir = scratch_dir("json");
+    let input = dir.join("frame.json");
+    let output = dir.join("out.png");
+    std::fs::write(&input, JSON_FRAME).expect("write input");
+
+    let status = cli()
+        .args([input.to_str().unwrap(), "-o", output.to_str().unwrap(), "--size", "64x32", "--backend", "software"])
+        .status()
+        .expect("run cli");
+    assert!(status.success());
+    assert_eq!(load(&output), (64, 32));
+}
+
+#[test]
+fn renders_each_recorded_frame() {
+    let dir = scratch_dir("recording");
+    let input = dir.join("session.vcdl");
+    let mut recorder = RecordingRenderer::new(NullRenderer, Vec::new(), 48, 24).expect("header");
+    for i in 0..3 {
+        recorder.render_at(&[DrawCmd::Clear { color: Rgba::BLACK }], Duration::from_millis(16 * i));
+    }
+    let (_, bytes) = recorder.finish().expect("finish");
+    std::fs::write(&input, bytes).expect("write recording");
+
+    let output = dir.join("shot.png");
+    let status = cli()
+        .args([input.to_str().unwrap(), "-o", output.to_str().unwrap(), "--backend", "software"])
+        .status()
+        .expect("run cli");
+    assert!(status.success());
+    for i in 0..3 {
+        assert_eq!(load(&dir.join(format!("shot_{i:05}.png"))), (48, 24));
+    }
+}
+
+#[test]
+fn rejects_unknown_options() {
+    let status = cli().args(["frame.json", "--bogus"]).status().expect("run cli");
+    assert_eq!(status.code(), Some(2));
+}
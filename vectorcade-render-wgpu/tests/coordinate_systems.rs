@@ -0,0 +1,82 @@
+//! Tests for CoordinateSystem projections and their effect on the CPU renderers.
+
+use glam::Vec2;
+use vectorcade_render_wgpu::{CoordinateSystem, SoftwareRenderer, SvgRenderer, VectorRenderer, YAxis};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+
+fn line(a: Vec2, b: Vec2, width_px: f32) -> DrawCmd {
+    DrawCmd::Line(Line2 { a, b, stroke: Stroke::new(Rgba::WHITE, width_px) })
+}
+
+/// Every number in `svg`, in document order.
+fn numbers(svg: &str) -> Vec<f32> {
+    svg.split(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-')).filter_map(|s| s.parse().ok()).collect()
+}
+
+fn assert_close(a: &[f32], b: &[f32]) {
+    assert_eq!(a.len(), b.len());
+    for (x, y) in a.iter().zip(b) {
+        assert!((x - y).abs() < 0.01, "{x} != {y}");
+    }
+}
+
+#[test]
+fn projections_map_corners_to_ndc() {
+    let corner = |coords: CoordinateSystem, p: Vec2| coords.projection(64, 32).transform_point2(p);
+    assert_eq!(CoordinateSystem::default().projection(64, 32), glam::Mat3::IDENTITY);
+    assert_eq!(corner(CoordinateSystem::Normalized(YAxis::Down), Vec2::new(0.5, 0.5)), Vec2::new(0.5, -0.5));
+    assert_eq!(corner(CoordinateSystem::Pixels(YAxis::Down), Vec2::ZERO), Vec2::new(-1.0, 1.0));
+    assert_eq!(corner(CoordinateSystem::Pixels(YAxis::Down), Vec2::new(64.0, 32.0)), Vec2::new(1.0, -1.0));
+    assert_eq!(corner(CoordinateSystem::Pixels(YAxis::Up), Vec2::ZERO), Vec2::new(-1.0, -1.0));
+    let world = CoordinateSystem::World { min: Vec2::new(0.0, 0.0), max: Vec2::new(4.0, 3.0), y_axis: YAxis::Up };
+    assert_eq!(corner(world, Vec2::new(2.0, 1.5)), Vec2::ZERO);
+    assert_eq!(corner(world, Vec2::new(4.0, 3.0)), Vec2::ONE);
+    let empty = CoordinateSystem::World { min: Vec2::ONE, max: Vec2::ONE, y_axis: YAxis::Up };
+    assert_eq!(empty.projection(64, 32), glam::Mat3::IDENTITY);
+}
+
+/// A line authored in y-down pixels renders like its normalized equivalent, at the same width.
+#[test]
+fn pixel_space_matches_normalized_space() {
+    let mut normalized = SoftwareRenderer::new(64, 32);
+    normalized.render(&[DrawCmd::Clear { color: Rgba::BLACK }, line(Vec2::new(-1.0, 0.5), Vec2::new(1.0, 0.5), 4.0)]);
+
+    let mut pixels = SoftwareRenderer::new(64, 32);
+    pixels.set_coordinate_system(CoordinateSystem::Pixels(YAxis::Down));
+    pixels.render(&[DrawCmd::Clear { color: Rgba::BLACK }, line(Vec2::new(0.0, 8.0), Vec2::new(64.0, 8.0), 4.0)]);
+
+    let image = pixels.image();
+    assert_eq!(image.pixel(32, 7), [255, 255, 255, 255], "stroke center at y = 8");
+    assert_eq!(image.pixel(32, 12), [0, 0, 0, 255], "4 px wide, not scaled by the projection");
+    for y in 0..32 {
+        assert_eq!(image.pixel(32, y), normalized.image().pixel(32, y), "row {y}");
+    }
+}
+
+/// Stroke widths and text sizes stay in pixels under a world rectangle, and y-down text stays upright.
+#[test]
+fn svg_output_is_independent_of_the_coordinate_system() {
+    let frame = |pos: Vec2, a: Vec2, b: Vec2| {
+        vec![
+            line(a, b, 3.0),
+            DrawCmd::Text {
+                pos,
+                text: "HI".to_string(),
+                size_px: 24.0,
+                color: Rgba::WHITE,
+                style: vectorcade_shared::font::FontStyleId::ATARI,
+            },
+        ]
+    };
+    let mut normalized = SvgRenderer::new(240, 240);
+    normalized.render(&frame(Vec2::new(-0.5, 0.0), Vec2::new(-1.0, -1.0), Vec2::new(0.0, 0.5)));
+
+    let mut world = SvgRenderer::new(240, 240);
+    let (min, max) = (Vec2::ZERO, Vec2::new(3.0, 3.0));
+    world.set_coordinate_system(CoordinateSystem::World { min, max, y_axis: YAxis::Down });
+    world.render(&frame(Vec2::new(0.75, 1.5), Vec2::new(0.0, 3.0), Vec2::new(1.5, 0.75)));
+
+    assert!(world.document().contains(r#"stroke-width="3""#));
+    assert_close(&numbers(world.document()), &numbers(normalized.document()));
+}
@@ -0,0 +1,78 @@
+//! Tests for the golden-image testing harness.
+
+use vectorcade_render_wgpu::RgbaImage;
+use vectorcade_render_wgpu::testing::{Golden, Tolerance, diff_images};
+
+fn solid(width: u32, height: u32, rgba: [u8; 4]) -> RgbaImage {
+    RgbaImage::from_pixels(width, height, rgba.repeat((width * height) as usize)).expect("size matches")
+}
+
+fn scratch_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("vectorcade-golden-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn identical_images_have_no_diff() {
+    let image = solid(4, 4, [10, 200, 30, 255]);
+    let diff = diff_images(&image, &image, 0.1).expect("same size");
+    assert_eq!((diff.differing, diff.total), (0, 16));
+    assert_eq!(diff.max_delta, 0.0);
+}
+
+#[test]
+fn small_shades_pass_and_wrong_colors_fail() {
+    let expected = solid(2, 2, [128, 128, 128, 255]);
+    let close = diff_images(&solid(2, 2, [130, 127, 128, 255]), &expected, 0.1).expect("same size");
+    assert_eq!(close.differing, 0);
+
+    let wrong = diff_images(&solid(2, 2, [255, 0, 0, 255]), &expected, 0.1).expect("same size");
+    assert_eq!(wrong.differing, 4);
+    assert_eq!(wrong.image.pixel(0, 0), [255, 0, 0, 255], "diff image marks differing pixels red");
+}
+
+#[test]
+fn size_mismatch_is_not_comparable() {
+    assert!(diff_images(&solid(2, 2, [0; 4]), &solid(2, 3, [0; 4]), 0.1).is_none());
+}
+
+#[test]
+fn missing_reference_fails_outside_bless_mode() {
+    let dir = scratch_dir("record");
+    let golden = Golden::new(&dir).with_bless(false);
+    let err = golden.check("scene", &solid(3, 3, [0, 0, 0, 255])).expect_err("no reference");
+    assert!(err.contains("VECTORCADE_BLESS=1"), "{err}");
+    assert!(!golden.reference_path("scene").exists());
+    assert!(dir.join("scene.actual.png").exists());
+
+    golden.clone().with_bless(true).check("scene", &solid(3, 3, [0, 0, 0, 255])).expect("records reference");
+    golden.check("scene", &solid(3, 3, [0, 0, 0, 255])).expect("matches the recorded reference");
+}
+
+#[test]
+fn mismatch_writes_actual_and_diff_images() {
+    let dir = scratch_dir("mismatch");
+    let golden = Golden::new(&dir).with_bless(false);
+    golden.clone().with_bless(true).check("scene", &solid(3, 3, [0, 0, 0, 255])).expect("records reference");
+
+    let err = golden.check("scene", &solid(3, 3, [255, 255, 255, 255])).expect_err("colors differ");
+    assert!(err.contains("9 of 9 pixels differ"), "{err}");
+    assert!(dir.join("scene.actual.png").exists());
+    assert!(dir.join("scene.diff.png").exists());
+
+    let tolerant = golden.with_tolerance(Tolerance { threshold: 1.0, max_diff_ratio: 0.0 });
+    tolerant.check("scene", &solid(3, 3, [255, 255, 255, 255])).expect("within tolerance");
+    assert!(!dir.join("scene.diff.png").exists(), "stale diff images are removed on success");
+}
+
+#[test]
+fn bless_overwrites_reference() {
+    let dir = scratch_dir("bless");
+    let golden = Golden::new(&dir).with_bless(true);
+    golden.check("scene", &solid(2, 2, [0, 0, 0, 255])).expect("write");
+    golden.check("scene", &solid(2, 2, [255, 255, 255, 255])).expect("overwrite");
+
+    let reference = RgbaImage::load_png(golden.reference_path("scene")).expect("load");
+    assert_eq!(reference.pixel(1, 1), [255, 255, 255, 255]);
+}
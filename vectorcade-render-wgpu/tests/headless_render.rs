@@ -4,216 +4,53 @@
 
 #![cfg(feature = "wgpu-backend")]
 
-use glam::Vec2;
-use vectorcade_render_wgpu::tessellate::{tessellate_line, tessellate_polyline, Geometry};
-use vectorcade_shared::draw::{Line2, Stroke};
+use glam::{Mat3, Vec2};
+use vectorcade_render_wgpu::{VectorRenderer, WgpuRenderer};
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
 use vectorcade_shared::Rgba;
 
 const WIDTH: u32 = 256;
 const HEIGHT: u32 = 256;
 
-/// Render a simple scene to a texture and verify pixels are non-black.
+/// Render a simple scene with a headless renderer and verify pixels are non-black.
 #[test]
 fn headless_render_produces_visible_output() {
     pollster::block_on(async {
-        // 1. Setup wgpu
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: None,
-                force_fallback_adapter: false,
-            })
-            .await
-            .expect("No adapter");
-
-        let (device, queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor::default(), None)
-            .await
-            .expect("No device");
-
-        // 2. Create render target texture
-        let format = wgpu::TextureFormat::Rgba8Unorm;
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Render Target"),
-            size: wgpu::Extent3d { width: WIDTH, height: HEIGHT, depth_or_array_layers: 1 },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
-            view_formats: &[],
-        });
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-        // 3. Create simple pipeline (same as WgpuRenderer but without MSAA for simplicity)
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Test Shader"),
-            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
-        });
+        // 1. Setup a renderer that owns its render target
+        let mut renderer = WgpuRenderer::new_headless(WIDTH, HEIGHT).await.expect("No adapter");
+        assert_eq!(renderer.size(), (WIDTH, HEIGHT));
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Test Pipeline"),
-            layout: None,
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: 24, // 2 floats pos + 4 floats color
-                    step_mode: wgpu::VertexStepMode::Vertex,
-                    attributes: &[
-                        wgpu::VertexAttribute { offset: 0, shader_location: 0, format: wgpu::VertexFormat::Float32x2 },
-                        wgpu::VertexAttribute { offset: 8, shader_location: 1, format: wgpu::VertexFormat::Float32x4 },
-                    ],
-                }],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
+        // 2. Draw a white triangle and a red horizontal line
+        let cmds = vec![
+            DrawCmd::Clear { color: Rgba::BLACK },
+            DrawCmd::Polyline {
+                pts: vec![Vec2::new(0.0, 0.5), Vec2::new(-0.5, -0.5), Vec2::new(0.5, -0.5)],
+                closed: true,
+                stroke: Stroke::new(Rgba::WHITE, 6.0),
             },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
-
-        // 4. Tessellate a white triangle in the center
-        let mut geom = Geometry::new();
-        // px_to_ndc = 1.0 since we're using NDC-scale stroke widths in this test
-        let px_to_ndc = 1.0;
-
-        // Draw a large white triangle
-        let stroke = Stroke::new(Rgba::WHITE, 0.05); // stroke width in NDC
-        tessellate_polyline(
-            &[
-                [0.0, 0.5],   // top
-                [-0.5, -0.5], // bottom-left
-                [0.5, -0.5],  // bottom-right
-            ],
-            true, // closed
-            &stroke,
-            None,
-            px_to_ndc,
-            &mut geom,
-        );
-
-        // Also draw some lines
-        tessellate_line(
-            &Line2 {
+            DrawCmd::Line(Line2 {
                 a: Vec2::new(-0.8, 0.0),
                 b: Vec2::new(0.8, 0.0),
-                stroke: Stroke::new(Rgba(1.0, 0.0, 0.0, 1.0), 0.02), // red horizontal line
-            },
-            None,
-            px_to_ndc,
-            &mut geom,
-        );
-
-        println!("Tessellated {} vertices, {} indices", geom.vertices.len(), geom.indices.len());
-        assert!(!geom.vertices.is_empty(), "Tessellation should produce vertices");
-        assert!(!geom.indices.is_empty(), "Tessellation should produce indices");
-
-        // 5. Create GPU buffers
-        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Vertex Buffer"),
-            size: (geom.vertices.len() * 24) as u64,
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-        queue.write_buffer(&vertex_buffer, 0, bytemuck::cast_slice(&geom.vertices));
-
-        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Index Buffer"),
-            size: (geom.indices.len() * 4) as u64,
-            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-        queue.write_buffer(&index_buffer, 0, bytemuck::cast_slice(&geom.indices));
-
-        // 6. Render
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
-        {
-            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Test Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                ..Default::default()
-            });
-            pass.set_pipeline(&pipeline);
-            pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-            pass.draw_indexed(0..geom.indices.len() as u32, 0, 0..1);
-        }
-
-        // 7. Copy texture to buffer for readback
-        let bytes_per_row = 4 * WIDTH; // RGBA
-        let padded_bytes_per_row = (bytes_per_row + 255) & !255; // align to 256
-        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Output Buffer"),
-            size: (padded_bytes_per_row * HEIGHT) as u64,
-            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-            mapped_at_creation: false,
-        });
-
-        encoder.copy_texture_to_buffer(
-            wgpu::TexelCopyTextureInfo {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            wgpu::TexelCopyBufferInfo {
-                buffer: &output_buffer,
-                layout: wgpu::TexelCopyBufferLayout {
-                    offset: 0,
-                    bytes_per_row: Some(padded_bytes_per_row),
-                    rows_per_image: Some(HEIGHT),
-                },
-            },
-            wgpu::Extent3d { width: WIDTH, height: HEIGHT, depth_or_array_layers: 1 },
-        );
-
-        queue.submit(Some(encoder.finish()));
-
-        // 8. Read back pixels
-        let buffer_slice = output_buffer.slice(..);
-        let (tx, rx) = std::sync::mpsc::channel();
-        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-            tx.send(result).unwrap();
-        });
-        device.poll(wgpu::Maintain::Wait);
-        rx.recv().unwrap().expect("Failed to map buffer");
+                stroke: Stroke::new(Rgba(1.0, 0.0, 0.0, 1.0), 3.0),
+            }),
+        ];
+        let stats = renderer.render(&cmds);
+        assert_eq!(stats.polylines, 1);
+        assert_eq!(stats.lines, 1);
 
-        let data = buffer_slice.get_mapped_range();
+        // 3. Read back pixels
+        let image = renderer.capture_frame().expect("capture failed");
+        assert_eq!((image.width, image.height), (WIDTH, HEIGHT));
 
         // Count non-black pixels
         let mut non_black_count = 0;
         let mut sample_pixels = Vec::new();
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                let offset = (y * padded_bytes_per_row + x * 4) as usize;
-                let r = data[offset];
-                let g = data[offset + 1];
-                let b = data[offset + 2];
-                if r > 0 || g > 0 || b > 0 {
-                    non_black_count += 1;
-                    if sample_pixels.len() < 10 {
-                        sample_pixels.push((x, y, r, g, b));
-                    }
+        for (i, px) in image.pixels.chunks_exact(4).enumerate() {
+            let (r, g, b) = (px[0], px[1], px[2]);
+            if r > 0 || g > 0 || b > 0 {
+                non_black_count += 1;
+                if sample_pixels.len() < 10 {
+                    sample_pixels.push((i as u32 % WIDTH, i as u32 / WIDTH, r, g, b));
                 }
             }
         }
@@ -225,27 +62,310 @@ fn headless_render_produces_visible_output() {
     });
 }
 
-const SHADER: &str = r#"
-struct VertexInput {
-    @location(0) position: vec2<f32>,
-    @location(1) color: vec4<f32>,
-};
-
-struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: vec4<f32>,
-};
-
-@vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
-    var out: VertexOutput;
-    out.clip_position = vec4<f32>(in.position, 0.0, 1.0);
-    out.color = in.color;
-    return out;
+/// Headless renderers keep rendering after a resize.
+#[test]
+fn headless_render_survives_resize() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(64, 64).await.expect("No adapter");
+        renderer.resize(128, 32);
+        assert_eq!(renderer.size(), (128, 32));
+
+        let texture = renderer.target_texture().expect("headless renderer owns a texture");
+        assert_eq!((texture.width(), texture.height()), (128, 32));
+
+        let cmds = [DrawCmd::Line(Line2 {
+            a: Vec2::new(-1.0, 0.0),
+            b: Vec2::new(1.0, 0.0),
+            stroke: Stroke::new(Rgba::WHITE, 4.0),
+        })];
+        assert_eq!(renderer.render(&cmds).lines, 1);
+    });
+}
+
+/// Captured frames match the clear color where nothing was drawn.
+#[test]
+fn capture_frame_reads_clear_color() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(32, 16).await.expect("No adapter");
+        renderer.render(&[DrawCmd::Clear { color: Rgba(0.0, 0.0, 1.0, 1.0) }]);
+
+        let image = renderer.capture_frame().expect("capture failed");
+        assert_eq!(image.pixels.len(), 32 * 16 * 4, "rows should be tightly packed");
+        assert_eq!(image.pixel(0, 0), [0, 0, 255, 255]);
+        assert_eq!(image.pixel(31, 15), [0, 0, 255, 255]);
+    });
+}
+
+/// MSAA sample counts fall back to supported values and can be switched at runtime.
+#[test]
+fn sample_count_falls_back_and_switches() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless_with_sample_count(64, 64, 3).await.expect("No adapter");
+        let supported = renderer.supported_sample_counts().to_vec();
+        assert!(supported.contains(&1));
+        assert!(supported.contains(&renderer.sample_count()));
+        assert!(renderer.sample_count() <= 2, "3x is never valid and must fall back");
+
+        assert_eq!(renderer.set_sample_count(1), 1);
+        let cmds = [DrawCmd::Line(Line2 {
+            a: Vec2::new(-1.0, 0.0),
+            b: Vec2::new(1.0, 0.0),
+            stroke: Stroke::new(Rgba::WHITE, 4.0),
+        })];
+        renderer.render(&cmds);
+        assert_eq!(renderer.capture_frame().expect("capture failed").pixel(32, 31), [255, 255, 255, 255]);
+
+        let max = *supported.last().unwrap();
+        assert_eq!(renderer.set_sample_count(16), max);
+        assert_eq!(renderer.render(&cmds).lines, 1);
+    });
+}
+
+/// Instanced lines cover the same pixels as tessellated lines.
+#[test]
+fn instanced_lines_match_tessellated_lines() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(64, 64).await.expect("No adapter");
+        let cmds = [
+            DrawCmd::Clear { color: Rgba::BLACK },
+            DrawCmd::Line(Line2 {
+                a: Vec2::new(-0.5, -0.25),
+                b: Vec2::new(0.5, 0.25),
+                stroke: Stroke::new(Rgba::WHITE, 6.0),
+            }),
+        ];
+        renderer.render(&cmds);
+        let tessellated = renderer.capture_frame().expect("capture failed");
+
+        renderer.set_instanced_lines(true);
+        assert!(renderer.instanced_lines());
+        assert_eq!(renderer.render(&cmds).lines, 1);
+        let instanced = renderer.capture_frame().expect("capture failed");
+
+        assert_eq!(instanced.pixel(32, 32), [255, 255, 255, 255], "segment center is fully covered");
+        assert_eq!(instanced.pixel(2, 2), [0, 0, 0, 255], "corners stay background");
+        let lit = |image: &vectorcade_render_wgpu::RgbaImage| {
+            image.pixels.chunks_exact(4).filter(|p| p[0] > 127).count()
+        };
+        let (a, b) = (lit(&tessellated) as f32, lit(&instanced) as f32);
+        assert!((a - b).abs() / a < 0.1, "coverage differs: {a} vs {b} pixels");
+    });
+}
+
+/// Retained meshes are drawn with per-draw transforms and tints beneath the display list.
+#[test]
+fn retained_mesh_draws_with_transform_and_tint() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(64, 64).await.expect("No adapter");
+        renderer.set_glow_intensity(0.0);
+        let bar = renderer.create_mesh(&[DrawCmd::Line(Line2 {
+            a: Vec2::new(-0.2, 0.0),
+            b: Vec2::new(0.2, 0.0),
+            stroke: Stroke::new(Rgba::WHITE, 6.0),
+        })]);
+        let empty = renderer.create_mesh(&[]);
+        assert_ne!(bar, empty);
+
+        let red = Rgba(1.0, 0.0, 0.0, 1.0);
+        renderer.draw_mesh(bar, Mat3::from_translation(Vec2::new(-0.5, 0.5)), Rgba::WHITE);
+        renderer.draw_mesh(bar, Mat3::from_translation(Vec2::new(0.5, -0.5)), red);
+        renderer.draw_mesh(empty, Mat3::IDENTITY, Rgba::WHITE);
+        renderer.render(&[DrawCmd::Clear { color: Rgba::BLACK }]);
+        let image = renderer.capture_frame().expect("capture failed");
+        assert_eq!(image.pixel(16, 16), [255, 255, 255, 255], "first instance, untinted");
+        assert_eq!(image.pixel(48, 48), [255, 0, 0, 255], "second instance, tinted red");
+        assert_eq!(image.pixel(32, 32), [0, 0, 0, 255], "untranslated position stays empty");
+
+        renderer.render(&[DrawCmd::Clear { color: Rgba::BLACK }]);
+        let image = renderer.capture_frame().expect("capture failed");
+        assert_eq!(image.pixel(16, 16), [0, 0, 0, 255], "draws last one frame");
+
+        assert!(renderer.destroy_mesh(bar));
+        assert!(!renderer.destroy_mesh(bar));
+        renderer.draw_mesh(bar, Mat3::IDENTITY, Rgba::WHITE);
+        renderer.render(&[DrawCmd::Clear { color: Rgba::BLACK }]);
+    });
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+/// Mesh strokes get the same aspect correction as immediate ones on a non-square target.
+#[test]
+fn retained_mesh_matches_immediate_width_on_wide_target() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(512, 128).await.expect("No adapter");
+        renderer.set_glow_intensity(0.0);
+        let line = DrawCmd::Line(Line2 {
+            a: Vec2::new(0.0, -0.5),
+            b: Vec2::new(0.0, 0.5),
+            stroke: Stroke::new(Rgba::WHITE, 8.0),
+        });
+        let row_width = |image: &vectorcade_render_wgpu::RgbaImage| {
+            (0..512).filter(|&x| image.pixel(x, 64)[0] > 127).count()
+        };
+
+        renderer.render(&[DrawCmd::Clear { color: Rgba::BLACK }, line.clone()]);
+        let immediate = row_width(&renderer.capture_frame().expect("capture failed"));
+
+        let mesh = renderer.create_mesh(&[line]);
+        renderer.draw_mesh(mesh, Mat3::IDENTITY, Rgba::WHITE);
+        renderer.render(&[DrawCmd::Clear { color: Rgba::BLACK }]);
+        let retained = row_width(&renderer.capture_frame().expect("capture failed"));
+
+        assert!((7..=9).contains(&immediate), "immediate line is {immediate} px wide");
+        assert_eq!(retained, immediate, "mesh line width");
+    });
+}
+
+/// Glowing strokes light a halo beside them; no glow intensity or stroke glow leaves it dark.
+#[test]
+fn glow_lights_pixels_beside_the_stroke() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(64, 64).await.expect("No adapter");
+        let stroke = |glow| DrawCmd::Line(Line2 {
+            a: Vec2::new(-0.8, 0.0),
+            b: Vec2::new(0.8, 0.0),
+            stroke: Stroke { glow, ..Stroke::new(Rgba::WHITE, 2.0) },
+        });
+        // Row 26 is five pixels above the 2 px stroke covering rows 31 and 32.
+        let halo = |renderer: &mut WgpuRenderer, cmd: DrawCmd| {
+            renderer.render(&[DrawCmd::Clear { color: Rgba::BLACK }, cmd]);
+            renderer.capture_frame().expect("capture failed").pixel(32, 26)
+        };
+
+        let lit = halo(&mut renderer, stroke(1.0));
+        assert!(lit[0] > 16, "halo beside a glowing stroke: {lit:?}");
+        assert_eq!(halo(&mut renderer, stroke(0.0)), [0, 0, 0, 255], "stroke without glow");
+        renderer.set_glow_intensity(0.0);
+        assert_eq!(halo(&mut renderer, stroke(1.0)), [0, 0, 0, 255], "glow intensity 0");
+    });
+}
+
+/// GPU stats count uploaded bytes, buffer growth and draw calls.
+#[test]
+fn stats_report_uploads_and_draw_calls() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(64, 64).await.expect("No adapter");
+        renderer.set_glow_intensity(0.0);
+        let line = |y: f32| {
+            DrawCmd::Line(Line2 { a: Vec2::new(-0.5, y), b: Vec2::new(0.5, y), stroke: Stroke::new(Rgba::WHITE, 2.0) })
+        };
+
+        let stats = renderer.render(&[line(0.0), line(0.5)]);
+        assert_eq!(stats.draw_calls, 2, "one merged mesh batch and the composite");
+        assert_eq!(stats.buffer_reallocations, 0);
+        let transform_bytes = 48;
+        assert_eq!(stats.bytes_uploaded, u64::from(stats.vertices * 40 + stats.indices * 4) + transform_bytes);
+
+        let many: Vec<DrawCmd> = (0..4000).map(|i| line(i as f32 / 4000.0)).collect();
+        assert!(renderer.render(&many).buffer_reallocations > 0, "buffers grow for a larger frame");
+        assert_eq!(renderer.render(&many).buffer_reallocations, 0, "and are reused afterwards");
+
+        renderer.set_glow_intensity(1.0);
+        assert!(renderer.render(&[line(0.0)]).draw_calls > 2, "blur passes count as draw calls");
+    });
+}
+
+/// GPU pass timings arrive a few frames later, or profiling stays off without timestamp support.
+#[test]
+fn gpu_profiling_reports_pass_times_when_supported() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(64, 64).await.expect("No adapter");
+        let supported = renderer.gpu_profiling_supported();
+        assert_eq!(renderer.set_gpu_profiling(true), supported);
+        let stroke = Stroke::new(Rgba::WHITE, 2.0);
+        let cmds = [DrawCmd::Line(Line2 { a: Vec2::new(-0.5, 0.0), b: Vec2::new(0.5, 0.0), stroke })];
+
+        for _ in 0..10 {
+            renderer.render(&cmds);
+            renderer.device().poll(wgpu::Maintain::Wait);
+        }
+        let Some(timings) = renderer.gpu_timings() else {
+            assert!(!supported, "timings should arrive within a few frames");
+            return;
+        };
+        assert!(timings.scene_ms >= 0.0 && timings.composite_ms >= 0.0);
+        assert!(timings.glow_ms.is_some(), "glow is on by default");
+        assert_eq!(timings.persistence_ms, None, "persistence is off");
+        assert!(timings.total_ms() >= timings.scene_ms);
+
+        assert!(!renderer.set_gpu_profiling(false));
+        assert_eq!(renderer.gpu_timings(), None);
+    });
+}
+
+/// `try_render` reports the same stats as `render`; headless targets always have a frame.
+#[test]
+fn try_render_succeeds_on_headless_targets() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(64, 64).await.expect("No adapter");
+        let stroke = Stroke::new(Rgba::WHITE, 2.0);
+        let cmds = [DrawCmd::Line(Line2 { a: Vec2::new(-0.5, 0.0), b: Vec2::new(0.5, 0.0), stroke })];
+
+        let stats = renderer.try_render(&cmds).expect("headless frames cannot fail");
+        assert_eq!(stats.lines, 1);
+        assert!(stats.draw_calls > 0);
+        let surface_errors = (stats.surface_outdated, stats.surface_lost, stats.surface_timeouts);
+        assert_eq!(surface_errors, (0, 0, 0));
+    });
+}
+
+/// Surface and readback errors map onto `RenderError` variants that keep a readable message.
+#[test]
+fn surface_errors_convert_to_render_errors() {
+    use std::error::Error;
+    use vectorcade_render_wgpu::RenderError;
+
+    assert!(matches!(RenderError::from(wgpu::SurfaceError::Lost), RenderError::SurfaceLost));
+    assert!(matches!(RenderError::from(wgpu::SurfaceError::Outdated), RenderError::SurfaceOutdated));
+    assert!(matches!(RenderError::from(wgpu::SurfaceError::Timeout), RenderError::SurfaceTimeout));
+    assert!(matches!(RenderError::from(wgpu::SurfaceError::OutOfMemory), RenderError::OutOfMemory));
+    assert_eq!(RenderError::NoAdapter.to_string(), "no GPU or fallback adapter found");
+    assert!(RenderError::UnsupportedFormat.source().is_none());
+    let readback = RenderError::from(wgpu::BufferAsyncError);
+    assert!(matches!(readback, RenderError::Readback(_)));
+    assert!(readback.source().is_some());
+    assert!(readback.to_string().starts_with("failed to map the readback buffer"));
+}
+
+/// Headless renderers honour the adapter and MSAA options and ignore presentation ones.
+#[test]
+fn headless_renderer_accepts_config() {
+    use vectorcade_render_wgpu::RendererConfig;
+
+    pollster::block_on(async {
+        let config = RendererConfig {
+            power_preference: wgpu::PowerPreference::LowPower,
+            present_mode: wgpu::PresentMode::Immediate,
+            sample_count: 1,
+            ..RendererConfig::default()
+        };
+        let mut renderer = WgpuRenderer::new_headless_with_config(32, 32, config).await.expect("No adapter");
+        assert_eq!(renderer.sample_count(), 1);
+        assert_eq!(renderer.present_mode(), None);
+        assert_eq!(renderer.set_present_mode(wgpu::PresentMode::Mailbox), None);
+        assert_eq!(renderer.frame_latency(), None);
+    });
+}
+
+/// A renderer built on a caller's device draws into a texture of the caller's format.
+#[test]
+fn renderer_shares_an_existing_device() {
+    pollster::block_on(async {
+        let instance = wgpu::Instance::default();
+        let options = wgpu::RequestAdapterOptions { force_fallback_adapter: false, ..Default::default() };
+        let adapter = match instance.request_adapter(&options).await {
+            Some(adapter) => adapter,
+            None => {
+                let fallback = wgpu::RequestAdapterOptions { force_fallback_adapter: true, ..Default::default() };
+                instance.request_adapter(&fallback).await.expect("No adapter")
+            }
+        };
+        let (device, queue) = adapter.request_device(&Default::default(), None).await.expect("No device");
+        let format = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+        let mut renderer = WgpuRenderer::from_device(device.clone(), queue.clone(), format, 32, 16);
+        assert_eq!(renderer.target_texture().map(wgpu::Texture::format), Some(format));
+        renderer.render(&[DrawCmd::Clear { color: Rgba(0.0, 0.0, 1.0, 1.0) }]);
+        assert_eq!(renderer.capture_frame().expect("capture failed").pixel(0, 0), [0, 0, 255, 255]);
+        assert!(renderer.supported_sample_counts().contains(&4));
+    });
 }
-"#;
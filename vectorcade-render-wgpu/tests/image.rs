@@ -0,0 +1,50 @@
+//! Tests for RgbaImage and PNG encoding.
+
+use vectorcade_render_wgpu::RgbaImage;
+
+#[test]
+fn new_image_is_transparent_black() {
+    let image = RgbaImage::new(3, 2);
+    assert_eq!(image.pixels.len(), 3 * 2 * 4);
+    assert!(image.pixels.iter().all(|&b| b == 0));
+}
+
+#[test]
+fn from_pixels_checks_length() {
+    assert!(RgbaImage::from_pixels(2, 2, vec![0; 16]).is_some());
+    assert!(RgbaImage::from_pixels(2, 2, vec![0; 15]).is_none());
+}
+
+#[test]
+fn pixel_reads_rows_top_first() {
+    let mut pixels = vec![0; 2 * 2 * 4];
+    pixels[8..12].copy_from_slice(&[1, 2, 3, 4]); // (0, 1)
+    let image = RgbaImage::from_pixels(2, 2, pixels).unwrap();
+    assert_eq!(image.pixel(0, 1), [1, 2, 3, 4]);
+    assert_eq!(image.pixel(1, 0), [0, 0, 0, 0]);
+}
+
+#[test]
+fn png_round_trip() {
+    let pixels: Vec<u8> = (0..4 * 3 * 4).map(|i| (i * 5) as u8).collect();
+    let image = RgbaImage::from_pixels(4, 3, pixels).unwrap();
+
+    let mut encoded = Vec::new();
+    image.write_png(&mut encoded).unwrap();
+    assert_eq!(&encoded[1..4], b"PNG");
+
+    let mut reader = png::Decoder::new(encoded.as_slice()).read_info().unwrap();
+    let mut decoded = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut decoded).unwrap();
+    assert_eq!((info.width, info.height), (4, 3));
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(decoded, image.pixels);
+}
+
+#[test]
+fn png_decode_round_trip() {
+    let image = RgbaImage::from_pixels(2, 1, vec![1, 2, 3, 4, 250, 251, 252, 253]).unwrap();
+    let mut png = Vec::new();
+    image.write_png(&mut png).unwrap();
+    assert_eq!(RgbaImage::read_png(png.as_slice()).unwrap(), image);
+}
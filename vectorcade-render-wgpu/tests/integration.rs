@@ -1,8 +1,10 @@
 //! Integration tests for the full rendering pipeline.
 //!
-//! Tests all DrawCmd types with the NullRenderer to verify command processing.
+//! Tests all DrawCmd types with the NullRenderer to verify command processing,
+//! and checks game-like scenes against golden images in `tests/golden`.
 
 use glam::{Mat3, Vec2};
+use vectorcade_render_wgpu::testing::{Golden, render_software};
 use vectorcade_render_wgpu::{NullRenderer, VectorRenderer};
 use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
 use vectorcade_shared::font::FontStyleId;
@@ -40,10 +42,12 @@ fn white_stroke(width: f32) -> Stroke {
     Stroke::new(Rgba::WHITE, width)
 }
 
-#[test]
-fn renders_pong_like_scene() {
-    let mut renderer = NullRenderer;
-    let cmds = vec![
+fn golden() -> Golden {
+    Golden::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"))
+}
+
+fn pong_scene() -> Vec<DrawCmd> {
+    vec![
         DrawCmd::Clear { color: Rgba::BLACK },
         // Left paddle
         DrawCmd::Polyline {
@@ -80,18 +84,26 @@ fn renders_pong_like_scene() {
             color: Rgba::WHITE,
             style: FontStyleId::ATARI,
         },
-    ];
+    ]
+}
 
-    let stats = renderer.render(&cmds);
+#[test]
+fn renders_pong_like_scene() {
+    let mut renderer = NullRenderer;
+    let stats = renderer.render(&pong_scene());
     assert_eq!(stats.lines, 1, "should have 1 center line");
     assert_eq!(stats.polylines, 3, "should have 3 polylines (paddles + ball)");
     assert_eq!(stats.text_runs, 2, "should have 2 score texts");
 }
 
 #[test]
-fn renders_asteroids_like_scene() {
-    let mut renderer = NullRenderer;
-    let cmds = vec![
+#[cfg_attr(not(feature = "fonts"), ignore = "score text needs the fonts feature")]
+fn pong_like_scene_matches_golden() {
+    golden().assert_matches("pong", &render_software(&pong_scene(), 320, 240));
+}
+
+fn asteroids_scene() -> Vec<DrawCmd> {
+    vec![
         DrawCmd::Clear { color: Rgba::BLACK },
         // Ship (triangle)
         DrawCmd::PushTransform(Mat3::from_rotation_z(0.5)),
@@ -127,14 +139,53 @@ fn renders_asteroids_like_scene() {
             color: Rgba::WHITE,
             style: FontStyleId::ATARI,
         },
-    ];
+    ]
+}
 
-    let stats = renderer.render(&cmds);
+#[test]
+fn renders_asteroids_like_scene() {
+    let mut renderer = NullRenderer;
+    let stats = renderer.render(&asteroids_scene());
     assert_eq!(stats.lines, 1, "should have 1 bullet line");
     assert_eq!(stats.polylines, 2, "should have 2 polylines (ship + asteroid)");
     assert_eq!(stats.text_runs, 1, "should have 1 score text");
 }
 
+#[test]
+#[cfg_attr(not(feature = "fonts"), ignore = "score text needs the fonts feature")]
+fn asteroids_like_scene_matches_golden() {
+    golden().assert_matches("asteroids", &render_software(&asteroids_scene(), 320, 240));
+}
+
+/// Render `cmds` at 320x240 on the GPU without glow, like the software golden scenes.
+#[cfg(feature = "wgpu-backend")]
+fn render_gpu(cmds: &[DrawCmd]) -> vectorcade_render_wgpu::RgbaImage {
+    pollster::block_on(async {
+        let mut renderer = vectorcade_render_wgpu::WgpuRenderer::new_headless(320, 240).await.expect("No adapter");
+        renderer.set_glow_intensity(0.0);
+        renderer.render(cmds);
+        renderer.capture_frame().expect("capture failed")
+    })
+}
+
+/// The golden scenes are blessed from the software renderer; the GPU must agree
+/// with it up to anti-aliasing, so a divergence cannot be blessed unnoticed.
+#[test]
+#[cfg(feature = "wgpu-backend")]
+#[cfg_attr(not(feature = "fonts"), ignore = "score text needs the fonts feature")]
+fn golden_scenes_match_the_gpu() {
+    use vectorcade_render_wgpu::testing::{Tolerance, diff_images};
+
+    for (name, scene) in [("pong", pong_scene()), ("asteroids", asteroids_scene())] {
+        let gpu = render_gpu(&scene);
+        let software = render_software(&scene, 320, 240);
+        let tolerance = Tolerance::default();
+        let diff = diff_images(&gpu, &software, tolerance.threshold).expect("same size");
+        let ratio = diff.ratio();
+        assert!(ratio <= tolerance.max_diff_ratio, "{name}: {ratio:.4} of the pixels differ between GPU and software");
+    }
+}
+
 #[test]
 fn handles_nested_transforms() {
     let mut renderer = NullRenderer;
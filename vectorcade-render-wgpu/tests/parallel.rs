@@ -0,0 +1,56 @@
+//! Tests for parallel tessellation, which must match serial tessellation exactly.
+
+#![cfg(feature = "parallel")]
+
+use glam::{Mat3, Vec2};
+use vectorcade_render_wgpu::{SoftwareRenderer, VectorRenderer};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+use vectorcade_shared::font::FontStyleId;
+
+/// A particle explosion: thousands of short segments under nested transforms.
+fn explosion() -> Vec<DrawCmd> {
+    let mut cmds = vec![DrawCmd::Clear { color: Rgba::BLACK }];
+    for burst in 0..6 {
+        let center = Vec2::new(burst as f32 * 0.3 - 0.75, (burst % 2) as f32 * 0.4 - 0.2);
+        cmds.push(DrawCmd::PushTransform(Mat3::from_scale_angle_translation(Vec2::splat(0.5), burst as f32, center)));
+        for i in 0..500 {
+            let angle = i as f32 * 0.37;
+            let dir = Vec2::new(angle.cos(), angle.sin());
+            let r = 0.1 + (i % 17) as f32 * 0.03;
+            let color = Rgba(1.0, (i % 5) as f32 * 0.2, 0.2, 0.9);
+            cmds.push(DrawCmd::Line(Line2 { a: dir * r, b: dir * (r + 0.05), stroke: Stroke::new(color, 1.5) }));
+        }
+        cmds.push(DrawCmd::Polyline {
+            pts: vec![Vec2::new(-0.1, -0.1), Vec2::new(0.1, -0.1), Vec2::new(0.0, 0.12)],
+            closed: true,
+            stroke: Stroke::new(Rgba::WHITE, 2.0),
+        });
+        cmds.push(DrawCmd::PopTransform);
+    }
+    cmds.push(DrawCmd::Text {
+        pos: Vec2::new(-0.9, 0.8),
+        text: "SCORE 1200".to_string(),
+        size_px: 16.0,
+        color: Rgba::GREEN,
+        style: FontStyleId::DEFAULT,
+    });
+    cmds
+}
+
+#[test]
+fn parallel_matches_serial_output() {
+    let cmds = explosion();
+    assert!(cmds.len() >= 1024, "large enough to take the parallel path");
+
+    let mut parallel = SoftwareRenderer::new(160, 120);
+    let mut serial = SoftwareRenderer::new(160, 120);
+    serial.set_parallel(false);
+
+    for _ in 0..2 {
+        let (p, s) = (parallel.render(&cmds), serial.render(&cmds));
+        assert_eq!((p.lines, p.polylines, p.text_runs), (s.lines, s.polylines, s.text_runs));
+        assert_eq!(p.cache_hits + p.cache_misses, s.cache_hits + s.cache_misses);
+        assert!(parallel.image().pixels == serial.image().pixels, "images differ");
+    }
+}
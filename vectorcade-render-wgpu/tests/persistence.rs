@@ -0,0 +1,63 @@
+//! Phosphor persistence: additive accumulation, per-channel decay and reset.
+
+#![cfg(feature = "wgpu-backend")]
+
+use glam::Vec2;
+use vectorcade_render_wgpu::{VectorRenderer, WgpuRenderer};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+
+/// Linear RGB of the captured pixel at the center of the target.
+fn center(renderer: &mut WgpuRenderer) -> [f32; 3] {
+    let px = renderer.capture_frame().expect("capture failed").pixel(16, 16);
+    let linear = |c: u8| {
+        let c = f32::from(c) / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    [linear(px[0]), linear(px[1]), linear(px[2])]
+}
+
+fn assert_close(actual: [f32; 3], expected: [f32; 3], what: &str) {
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 0.01, "{what}: {actual:?} != {expected:?}");
+    }
+}
+
+#[test]
+fn persistence_accumulates_decays_and_resets() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(32, 32).await.expect("No adapter");
+        renderer.set_glow_intensity(0.0);
+        let decay = [0.5, 0.25, 0.75];
+        renderer.set_persistence(Some(decay));
+        assert_eq!(renderer.persistence(), Some(decay));
+        let gray = Rgba(0.2, 0.2, 0.2, 1.0);
+        let stroke = DrawCmd::Line(Line2 {
+            a: Vec2::new(-1.0, 0.0),
+            b: Vec2::new(1.0, 0.0),
+            stroke: Stroke::new(gray, 8.0),
+        });
+        let empty = [DrawCmd::Clear { color: Rgba::BLACK }];
+        let lit = [DrawCmd::Clear { color: Rgba::BLACK }, stroke];
+
+        renderer.render(&lit);
+        assert_close(center(&mut renderer), [0.2; 3], "first frame");
+        renderer.render(&lit);
+        assert_close(center(&mut renderer), decay.map(|d| 0.2 + 0.2 * d), "held stroke adds up");
+
+        let mut expected = decay.map(|d| 0.2 + 0.2 * d);
+        for frame in 1..=3 {
+            renderer.render(&empty);
+            expected = [0, 1, 2].map(|i| expected[i] * decay[i]);
+            assert_close(center(&mut renderer), expected, &format!("empty frame {frame}"));
+        }
+
+        renderer.set_persistence(None);
+        assert_eq!(renderer.persistence(), None);
+        renderer.render(&empty);
+        assert_close(center(&mut renderer), [0.0; 3], "disabled");
+        renderer.set_persistence(Some(decay));
+        renderer.render(&empty);
+        assert_close(center(&mut renderer), [0.0; 3], "history cleared on re-enable");
+    });
+}
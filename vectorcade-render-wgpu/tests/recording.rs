@@ -0,0 +1,129 @@
+//! Tests for display-list recording and replay.
+
+use glam::{Mat3, Vec2};
+use std::time::Duration;
+use vectorcade_render_wgpu::record::VERSION;
+use vectorcade_render_wgpu::{NullRenderer, RecordingRenderer, Replayer, VectorRenderer};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+use vectorcade_shared::font::FontStyleId;
+
+fn scene() -> Vec<DrawCmd> {
+    let mut stroke = Stroke::new(Rgba(0.2, 1.0, 0.4, 0.75), 2.5);
+    stroke.glow = 0.5;
+    vec![
+        DrawCmd::Clear { color: Rgba::BLACK },
+        DrawCmd::PushTransform(Mat3::from_scale_angle_translation(Vec2::splat(0.5), 0.3, Vec2::new(0.1, -0.2))),
+        DrawCmd::Line(Line2 { a: Vec2::new(-1.0, 0.0), b: Vec2::new(1.0, 0.25), stroke: Stroke::new(Rgba::WHITE, 1.0) }),
+        DrawCmd::Polyline { pts: vec![Vec2::ZERO, Vec2::X, Vec2::Y], closed: true, stroke },
+        DrawCmd::PopTransform,
+        DrawCmd::Text {
+            pos: Vec2::new(-0.9, 0.8),
+            text: "SCORE 1200 \u{2665}".into(),
+            size_px: 16.0,
+            color: Rgba::GREEN,
+            style: FontStyleId::ATARI,
+        },
+    ]
+}
+
+fn record(frames: &[(Duration, Vec<DrawCmd>)]) -> Vec<u8> {
+    let mut recorder = RecordingRenderer::new(NullRenderer, Vec::new(), 640, 480).expect("header");
+    for (timestamp, cmds) in frames {
+        recorder.render_at(cmds, *timestamp);
+    }
+    assert_eq!(recorder.frames(), frames.len() as u64);
+    let (_, bytes) = recorder.finish().expect("finish");
+    bytes
+}
+
+#[test]
+fn recording_renders_through_inner_renderer() {
+    let mut recorder = RecordingRenderer::new(NullRenderer, Vec::new(), 640, 480).expect("header");
+    let stats = recorder.render(&scene());
+    assert_eq!((stats.lines, stats.polylines, stats.text_runs), (1, 1, 1));
+}
+
+#[test]
+fn round_trip_preserves_frames() {
+    let clear = vec![DrawCmd::Clear { color: Rgba::WHITE }];
+    let frames = vec![(Duration::ZERO, scene()), (Duration::from_micros(16_667), clear)];
+    let bytes = record(&frames);
+
+    let mut replayer = Replayer::new(bytes.as_slice()).expect("header");
+    assert_eq!(replayer.version(), VERSION);
+    assert_eq!(replayer.viewport(), (640, 480));
+    for (timestamp, cmds) in &frames {
+        let frame = replayer.next_frame().expect("frame").expect("not at end");
+        assert_eq!(frame.timestamp, *timestamp);
+        assert_eq!(format!("{:?}", frame.cmds), format!("{cmds:?}"));
+    }
+    assert!(replayer.next_frame().expect("end").is_none());
+}
+
+#[test]
+fn replay_feeds_any_renderer() {
+    let bytes = record(&[(Duration::ZERO, scene()), (Duration::from_millis(16), scene())]);
+
+    let mut replayer = Replayer::new(bytes.as_slice()).expect("header");
+    assert_eq!(replayer.replay_next(&mut NullRenderer).expect("frame").map(|s| s.lines), Some(1));
+    assert_eq!(replayer.replay_all(&mut NullRenderer).expect("frames"), 1);
+}
+
+#[test]
+fn rejects_foreign_data() {
+    assert!(Replayer::new(&b"PNG\x00\x01\x00\x00\x00"[..]).is_err());
+}
+
+#[test]
+fn truncated_frame_is_an_error() {
+    let mut bytes = record(&[(Duration::ZERO, scene())]);
+    bytes.truncate(bytes.len() - 3);
+
+    let mut replayer = Replayer::new(bytes.as_slice()).expect("header");
+    assert!(replayer.next_frame().is_err());
+}
+
+/// Fails every other read with `Interrupted`, as a signal-interrupted pipe may.
+struct Interrupting<'a> {
+    bytes: &'a [u8],
+    interrupt: bool,
+}
+
+impl std::io::Read for Interrupting<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(std::io::ErrorKind::Interrupted.into());
+        }
+        self.bytes.read(buf)
+    }
+}
+
+#[test]
+fn interrupted_reads_are_retried() {
+    let bytes = record(&[(Duration::ZERO, scene()), (Duration::from_millis(16), scene())]);
+    let reader = Interrupting { bytes: &bytes, interrupt: false };
+
+    let mut replayer = Replayer::new(reader).expect("header");
+    assert_eq!(replayer.replay_all(&mut NullRenderer).expect("frames"), 2);
+}
+
+/// Lengths a reader would reject fail at write time instead of producing an unreadable file.
+#[test]
+fn oversized_text_stops_recording() {
+    let text = |text: String| DrawCmd::Text {
+        pos: Vec2::ZERO,
+        text,
+        size_px: 8.0,
+        color: Rgba::WHITE,
+        style: FontStyleId::ATARI,
+    };
+    let mut recorder = RecordingRenderer::new(NullRenderer, Vec::new(), 640, 480).expect("header");
+    recorder.render_at(&[text("OK".into())], Duration::ZERO);
+    let stats = recorder.render_at(&[text("A".repeat((1 << 24) + 1))], Duration::from_millis(16));
+    assert_eq!(stats.text_runs, 1, "the frame is still rendered");
+    assert_eq!(recorder.frames(), 1);
+    let err = recorder.finish().map(|_| ()).expect_err("length over the limit");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,78 @@
+//! Tests for RenderStats counters, merging and rolling averages.
+
+use glam::Vec2;
+use std::time::Duration;
+use vectorcade_render_wgpu::{RenderStats, RollingStats, SoftwareRenderer, VectorRenderer};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+
+fn line(a: Vec2, b: Vec2) -> DrawCmd {
+    DrawCmd::Line(Line2 { a, b, stroke: Stroke::new(Rgba::WHITE, 2.0) })
+}
+
+#[test]
+fn software_renderer_reports_geometry() {
+    let mut r = SoftwareRenderer::new(64, 64);
+    let stats = r.render(&[line(Vec2::new(-0.5, 0.0), Vec2::new(0.5, 0.0))]);
+    assert!(stats.vertices > 0);
+    assert!(stats.triangles() > 0);
+    assert_eq!(stats.indices % 3, 0);
+    assert_eq!((stats.draw_calls, stats.bytes_uploaded), (0, 0), "GPU-only counters stay zero");
+    assert_eq!((stats.skipped, stats.failed), (0, 0));
+}
+
+#[test]
+fn degenerate_strokes_are_skipped() {
+    let mut r = SoftwareRenderer::new(64, 64);
+    let cmds = [
+        DrawCmd::Polyline { pts: vec![Vec2::ZERO], closed: false, stroke: Stroke::new(Rgba::WHITE, 2.0) },
+        line(Vec2::new(f32::NAN, 0.0), Vec2::ONE),
+        line(Vec2::ZERO, Vec2::ONE),
+    ];
+    let stats = r.render(&cmds);
+    assert_eq!((stats.lines, stats.polylines, stats.skipped), (2, 1, 2));
+    assert_eq!((stats.cache_hits, stats.cache_misses), (0, 0), "skipped polylines bypass the cache");
+    assert!(stats.vertices > 0, "the valid line is still drawn");
+}
+
+#[test]
+fn merge_adds_every_counter() {
+    let frame = RenderStats {
+        lines: 2,
+        vertices: 10,
+        indices: 12,
+        bytes_uploaded: 400,
+        draw_calls: 3,
+        tessellation_time: Duration::from_micros(50),
+        surface_outdated: 1,
+        ..RenderStats::default()
+    };
+    let mut total = frame;
+    total.merge(&frame);
+    total += frame;
+    assert_eq!((total.lines, total.vertices, total.triangles()), (6, 30, 12));
+    assert_eq!((total.bytes_uploaded, total.draw_calls), (1200, 9));
+    assert_eq!(total.tessellation_time, Duration::from_micros(150));
+    assert_eq!(total.surface_outdated, 3);
+}
+
+#[test]
+fn rolling_stats_average_the_last_frames() {
+    let frame = |lines: u32| RenderStats {
+        lines,
+        tessellation_time: Duration::from_millis(u64::from(lines)),
+        ..RenderStats::default()
+    };
+    let mut rolling = RollingStats::new(3);
+    assert!(rolling.is_empty());
+    assert_eq!(rolling.average(), RenderStats::default());
+
+    for lines in [100, 2, 4, 6] {
+        rolling.push(frame(lines));
+    }
+    assert_eq!(rolling.len(), 3, "the oldest frame is dropped");
+    assert_eq!(rolling.average().lines, 4);
+    assert_eq!(rolling.average().tessellation_time, Duration::from_millis(4));
+    assert_eq!(rolling.total().lines, 12);
+    assert_eq!(rolling.latest().map(|s| s.lines), Some(6));
+}
@@ -0,0 +1,116 @@
+//! Rendering into a caller-provided encoder and texture view.
+
+#![cfg(feature = "wgpu-backend")]
+
+use glam::Vec2;
+use vectorcade_render_wgpu::{LoadMode, WgpuRenderer};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+
+/// Width and height of the host texture; 64 RGBA8 pixels fill one aligned row.
+const SIZE: u32 = 64;
+
+fn host_texture(device: &wgpu::Device) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Host Texture"),
+        size: wgpu::Extent3d { width: SIZE, height: SIZE, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// Clear `view` to red, as a host's own pass would draw its UI.
+fn clear_red(encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Host Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::RED), store: wgpu::StoreOp::Store },
+        })],
+        ..Default::default()
+    });
+}
+
+/// Read the RGBA8 pixel at (`x`, `y`) of a `SIZE`x`SIZE` texture.
+fn pixel(renderer: &WgpuRenderer, texture: &wgpu::Texture, x: u32, y: u32) -> [u8; 4] {
+    let (device, queue) = (renderer.device(), renderer.queue());
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Readback"),
+        size: u64::from(SIZE * SIZE * 4),
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&Default::default());
+    let layout = wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(SIZE * 4), rows_per_image: None };
+    let copy = wgpu::TexelCopyBufferInfo { buffer: &buffer, layout };
+    encoder.copy_texture_to_buffer(texture.as_image_copy(), copy, texture.size());
+    queue.submit(std::iter::once(encoder.finish()));
+    buffer.slice(..).map_async(wgpu::MapMode::Read, |result| result.expect("map failed"));
+    device.poll(wgpu::Maintain::Wait);
+    let at = ((y * SIZE + x) * 4) as usize;
+    let data = buffer.slice(..).get_mapped_range();
+    [data[at], data[at + 1], data[at + 2], data[at + 3]]
+}
+
+/// `Load` blends the frame over the host's pixels; `Clear` replaces them.
+#[test]
+fn render_to_loads_or_clears_the_host_view() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(SIZE, SIZE).await.expect("No adapter");
+        renderer.set_glow_intensity(0.0);
+        let texture = host_texture(renderer.device());
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let stroke = Stroke::new(Rgba::WHITE, 4.0);
+        let cmds = [
+            DrawCmd::Clear { color: Rgba::BLACK },
+            DrawCmd::Line(Line2 { a: Vec2::new(-1.0, 0.0), b: Vec2::new(1.0, 0.0), stroke }),
+        ];
+
+        let mut encoder = renderer.device().create_command_encoder(&Default::default());
+        clear_red(&mut encoder, &view);
+        let stats = renderer.render_to(&mut encoder, &view, &cmds, LoadMode::Load);
+        renderer.queue().submit(std::iter::once(encoder.finish()));
+        assert_eq!(stats.lines, 1);
+        assert_eq!(pixel(&renderer, &texture, 32, 31), [255, 255, 255, 255], "stroke drawn over the host");
+        assert_eq!(pixel(&renderer, &texture, 2, 2), [255, 0, 0, 255], "host pixels kept");
+
+        let mut encoder = renderer.device().create_command_encoder(&Default::default());
+        clear_red(&mut encoder, &view);
+        renderer.render_to(&mut encoder, &view, &cmds, LoadMode::Clear);
+        renderer.queue().submit(std::iter::once(encoder.finish()));
+        assert_eq!(pixel(&renderer, &texture, 2, 2), [0, 0, 0, 255], "cleared to the display list's color");
+    });
+}
+
+/// Calls share upload buffers: within one encoder both views get the last frame, across submissions each its own.
+#[test]
+fn render_to_draws_one_frame_per_encoder() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(SIZE, SIZE).await.expect("No adapter");
+        renderer.set_glow_intensity(0.0);
+        let (first, second) = (host_texture(renderer.device()), host_texture(renderer.device()));
+        let views = [&first, &second].map(|t| t.create_view(&wgpu::TextureViewDescriptor::default()));
+        let clear = |color| [DrawCmd::Clear { color }];
+        let (green, blue) = (Rgba(0.0, 1.0, 0.0, 1.0), Rgba(0.0, 0.0, 1.0, 1.0));
+
+        let mut encoder = renderer.device().create_command_encoder(&Default::default());
+        renderer.render_to(&mut encoder, &views[0], &clear(green), LoadMode::Clear);
+        renderer.render_to(&mut encoder, &views[1], &clear(blue), LoadMode::Clear);
+        renderer.queue().submit(std::iter::once(encoder.finish()));
+        assert_eq!(pixel(&renderer, &first, 2, 2), [0, 0, 255, 255], "first call drew the last call's frame");
+        assert_eq!(pixel(&renderer, &second, 2, 2), [0, 0, 255, 255]);
+
+        for (view, color) in views.iter().zip([green, blue]) {
+            let mut encoder = renderer.device().create_command_encoder(&Default::default());
+            renderer.render_to(&mut encoder, view, &clear(color), LoadMode::Clear);
+            renderer.queue().submit(std::iter::once(encoder.finish()));
+        }
+        assert_eq!(pixel(&renderer, &first, 2, 2), [0, 255, 0, 255], "separate submissions keep their frames");
+        assert_eq!(pixel(&renderer, &second, 2, 2), [0, 0, 255, 255]);
+    });
+}
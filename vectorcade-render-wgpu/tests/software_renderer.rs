@@ -0,0 +1,110 @@
+//! Tests for SoftwareRenderer.
+
+use glam::Vec2;
+use vectorcade_render_wgpu::{SoftwareRenderer, VectorRenderer};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+
+fn line(a: Vec2, b: Vec2, width_px: f32) -> DrawCmd {
+    DrawCmd::Line(Line2 { a, b, stroke: Stroke::new(Rgba::WHITE, width_px) })
+}
+
+#[test]
+fn clear_fills_image() {
+    let mut r = SoftwareRenderer::new(8, 4);
+    r.render(&[DrawCmd::Clear { color: Rgba(1.0, 0.0, 0.0, 1.0) }]);
+
+    let image = r.image();
+    assert_eq!((image.width, image.height), (8, 4));
+    assert!(image.pixels.chunks_exact(4).all(|px| px == [255, 0, 0, 255]));
+}
+
+#[test]
+fn horizontal_line_covers_center_rows_only() {
+    let mut r = SoftwareRenderer::new(64, 64);
+    let stats = r.render(&[
+        DrawCmd::Clear { color: Rgba::BLACK },
+        line(Vec2::new(-0.5, 0.0), Vec2::new(0.5, 0.0), 4.0),
+    ]);
+    assert_eq!(stats.lines, 1);
+
+    let image = r.image();
+    assert_eq!(image.pixel(32, 31), [255, 255, 255, 255], "center of the stroke is fully covered");
+    assert_eq!(image.pixel(32, 10), [0, 0, 0, 255], "far from the stroke is background");
+    assert_eq!(image.pixel(2, 32), [0, 0, 0, 255], "beyond the line ends is background");
+}
+
+#[test]
+fn interior_has_no_seams_between_triangles() {
+    let mut r = SoftwareRenderer::new(64, 64);
+    r.render(&[DrawCmd::Clear { color: Rgba::BLACK }, line(Vec2::new(-0.8, -0.6), Vec2::new(0.7, 0.5), 12.0)]);
+
+    // Every pixel well inside the thick diagonal stroke must be fully lit, even
+    // where two tessellated triangles share an edge.
+    // Endpoints in pixels (y down): (6.4, 51.2) -> (54.4, 16.0).
+    let image = r.image();
+    for x in 10..=50u32 {
+        let t = (x as f32 + 0.5 - 6.4) / 48.0;
+        let y = 51.2 + t * (16.0 - 51.2);
+        assert_eq!(image.pixel(x, y as u32), [255, 255, 255, 255], "seam at x={x}");
+    }
+}
+
+#[test]
+fn edges_are_anti_aliased() {
+    let mut r = SoftwareRenderer::new(64, 64);
+    r.render(&[DrawCmd::Clear { color: Rgba::BLACK }, line(Vec2::new(-0.9, -0.8), Vec2::new(0.9, 0.3), 3.0)]);
+
+    let partial = r.image().pixels.chunks_exact(4).filter(|px| px[0] > 0 && px[0] < 255).count();
+    assert!(partial > 0, "diagonal edges should have partially covered pixels");
+}
+
+#[test]
+fn rendering_is_deterministic() {
+    let cmds = [
+        DrawCmd::Clear { color: Rgba::BLACK },
+        DrawCmd::Polyline {
+            pts: vec![Vec2::new(0.0, 0.6), Vec2::new(-0.5, -0.4), Vec2::new(0.5, -0.4)],
+            closed: true,
+            stroke: Stroke::new(Rgba::GREEN, 2.5),
+        },
+    ];
+    let mut a = SoftwareRenderer::new(96, 72);
+    let mut b = SoftwareRenderer::new(96, 72);
+    a.render(&cmds);
+    b.render(&cmds);
+    assert_eq!(a.image(), b.image());
+}
+
+#[test]
+fn resize_changes_image_size() {
+    let mut r = SoftwareRenderer::new(16, 16);
+    r.resize(32, 8);
+    r.render(&[DrawCmd::Clear { color: Rgba::BLACK }]);
+    assert_eq!(r.size(), (32, 8));
+    assert_eq!(r.image().pixels.len(), 32 * 8 * 4);
+}
+
+/// Strokes keep their pixel width along both axes on wide and tall images,
+/// including under a rotating transform.
+#[test]
+fn stroke_widths_are_equal_on_non_square_images() {
+    let rotated = glam::Mat3::from_angle(std::f32::consts::FRAC_PI_2);
+    let cross = [
+        DrawCmd::Clear { color: Rgba::BLACK },
+        line(Vec2::new(-0.5, 0.0), Vec2::new(0.5, 0.0), 6.0),
+        DrawCmd::PushTransform(rotated),
+        line(Vec2::new(-0.5, 0.0), Vec2::new(0.5, 0.0), 6.0),
+        DrawCmd::PopTransform,
+    ];
+    for (width, height) in [(160, 64), (64, 160)] {
+        let mut r = SoftwareRenderer::new(width, height);
+        r.render(&cross);
+        let image = r.image();
+        let lit = |px: [u8; 4]| px[0] > 127;
+        // Three eighths of the way in, the row and column each cross one arm only.
+        let row = (0..width).filter(|&x| lit(image.pixel(x, height * 3 / 8))).count();
+        let column = (0..height).filter(|&y| lit(image.pixel(width * 3 / 8, y))).count();
+        assert_eq!((row, column), (6, 6), "{width}x{height} arm widths");
+    }
+}
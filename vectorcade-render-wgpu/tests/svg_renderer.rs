@@ -0,0 +1,88 @@
+//! Tests for SvgRenderer.
+
+use glam::{Mat3, Vec2};
+use vectorcade_render_wgpu::{SvgRenderer, VectorRenderer};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+
+fn line(a: Vec2, b: Vec2, stroke: Stroke) -> DrawCmd {
+    DrawCmd::Line(Line2 { a, b, stroke })
+}
+
+#[test]
+fn document_has_canvas_and_background() {
+    let mut r = SvgRenderer::new(200, 100);
+    r.render(&[DrawCmd::Clear { color: Rgba(1.0, 0.0, 0.0, 1.0) }]);
+
+    let svg = r.document();
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains(r#"viewBox="0 0 200 100""#));
+    assert!(svg.contains(r##"<rect width="200" height="100" fill="#ff0000"/>"##));
+    assert!(svg.trim_end().ends_with("</svg>"));
+}
+
+#[test]
+fn line_maps_to_pixel_coordinates() {
+    let mut r = SvgRenderer::new(200, 100);
+    let stats = r.render(&[line(Vec2::new(-1.0, 1.0), Vec2::new(1.0, 0.0), Stroke::new(Rgba::WHITE, 2.5))]);
+    assert_eq!(stats.lines, 1);
+
+    let svg = r.document();
+    assert!(svg.contains(r##"<line x1="0" y1="0" x2="200" y2="50" stroke="#ffffff" stroke-width="2.5"/>"##), "{svg}");
+}
+
+#[test]
+fn translucent_strokes_carry_opacity() {
+    let mut r = SvgRenderer::new(100, 100);
+    r.render(&[line(Vec2::ZERO, Vec2::ONE, Stroke::new(Rgba(0.0, 1.0, 0.0, 0.5), 1.0))]);
+    assert!(r.document().contains(r##"stroke="#00ff00" stroke-width="1" stroke-opacity="0.5""##));
+}
+
+#[test]
+fn polylines_become_polyline_or_polygon() {
+    let pts = vec![Vec2::new(-1.0, -1.0), Vec2::new(0.0, 1.0), Vec2::new(1.0, -1.0)];
+    let stroke = || Stroke::new(Rgba::WHITE, 1.0);
+    let mut r = SvgRenderer::new(100, 100);
+    let stats = r.render(&[
+        DrawCmd::Polyline { pts: pts.clone(), closed: false, stroke: stroke() },
+        DrawCmd::Polyline { pts, closed: true, stroke: stroke() },
+    ]);
+    assert_eq!(stats.polylines, 2);
+
+    let svg = r.document();
+    assert!(svg.contains(r#"<polyline points="0,100 50,0 100,100""#), "{svg}");
+    assert!(svg.contains(r#"<polygon points="0,100 50,0 100,100""#), "{svg}");
+}
+
+#[test]
+fn transform_stack_is_applied_to_points() {
+    let stroke = || Stroke::new(Rgba::WHITE, 1.0);
+    let mut r = SvgRenderer::new(100, 100);
+    r.render(&[
+        DrawCmd::PushTransform(Mat3::from_translation(Vec2::new(0.5, 0.0))),
+        line(Vec2::ZERO, Vec2::new(0.5, 0.0), stroke()),
+        DrawCmd::PopTransform,
+        line(Vec2::ZERO, Vec2::new(0.5, 0.0), stroke()),
+    ]);
+
+    let svg = r.document();
+    assert!(svg.contains(r#"<line x1="75" y1="50" x2="100" y2="50""#), "{svg}");
+    assert!(svg.contains(r#"<line x1="50" y1="50" x2="75" y2="50""#), "{svg}");
+}
+
+#[cfg(feature = "fonts")]
+#[test]
+fn text_expands_to_glyph_paths() {
+    use vectorcade_shared::font::FontStyleId;
+
+    let mut r = SvgRenderer::new(320, 240);
+    let stats = r.render(&[DrawCmd::Text {
+        pos: Vec2::new(-0.5, 0.0),
+        text: "HI".to_string(),
+        size_px: 24.0,
+        color: Rgba::WHITE,
+        style: FontStyleId::ATARI,
+    }]);
+    assert_eq!(stats.text_runs, 1);
+    assert!(r.document().contains("<polyline") || r.document().contains("<polygon"));
+}
@@ -1,7 +1,7 @@
 //! Tests for tessellation module.
 
 use glam::Vec2;
-use vectorcade_render_wgpu::tessellate::{Geometry, tessellate_line, tessellate_polyline};
+use vectorcade_render_wgpu::tessellate::{Geometry, LineInstance, Vertex, tessellate_line, tessellate_polyline};
 use vectorcade_shared::Rgba;
 use vectorcade_shared::draw::{Line2, Stroke};
 
@@ -59,3 +59,77 @@ fn single_point_produces_no_geometry() {
     tessellate_polyline(&[[0.0, 0.0]], false, &white_stroke(0.1), None, PX_TO_NDC, &mut geom);
     assert!(geom.vertices.is_empty());
 }
+
+#[test]
+fn vertices_carry_stroke_glow() {
+    let stroke = Stroke { glow: 0.75, ..white_stroke(0.1) };
+    let mut geom = Geometry::new();
+    tessellate_polyline(&[[0.0, 0.0], [1.0, 0.0]], false, &stroke, None, PX_TO_NDC, &mut geom);
+
+    assert!(!geom.vertices.is_empty());
+    assert!(geom.vertices.iter().all(|v| v.glow == 0.75));
+}
+
+#[test]
+fn line_instance_applies_transform_and_keeps_pixel_width() {
+    let mut stroke = Stroke::new(Rgba(1.0, 0.5, 0.25, 0.8), 3.0);
+    stroke.glow = 0.6;
+    let line = Line2 { a: Vec2::ZERO, b: Vec2::new(0.5, 0.0), stroke };
+    let t = glam::Mat3::from_scale_angle_translation(Vec2::splat(2.0), 0.0, Vec2::new(0.1, 0.2));
+
+    let instance = LineInstance::new(&line, Some(&t));
+    assert_eq!(instance.a, [0.1, 0.2]);
+    assert_eq!(instance.b, [1.1, 0.2]);
+    assert_eq!(instance.width_px, 3.0, "width stays in pixels like tessellated strokes");
+    assert_eq!(instance.color, [1.0, 0.5, 0.25, 0.8]);
+    assert_eq!(instance.glow, 0.6);
+    assert_eq!(std::mem::size_of::<LineInstance>(), 40);
+}
+
+#[test]
+fn transforms_are_recorded_not_applied() {
+    let line = Line2 { a: Vec2::ZERO, b: Vec2::new(0.5, 0.0), stroke: white_stroke(0.1) };
+    let t = glam::Mat3::from_scale_angle_translation(Vec2::splat(3.0), 0.0, Vec2::new(0.2, 0.4));
+    let mut geom = Geometry::new();
+    tessellate_line(&line, Some(&t), PX_TO_NDC, &mut geom);
+    tessellate_line(&line, Some(&t), PX_TO_NDC, &mut geom);
+
+    assert_eq!(geom.transforms, vec![glam::Mat3::IDENTITY, t], "consecutive draws share a transform");
+    assert!(geom.vertices.iter().all(|v| v.transform == 1));
+    assert!(geom.vertices.iter().all(|v| v.position[1] == 0.0), "centerline stays untransformed");
+
+    for v in &geom.vertices {
+        let [x, y] = geom.resolve(v);
+        assert!((0.14..=1.76).contains(&x), "x {x} is scaled and translated");
+        assert!((y - 0.4).abs() <= 0.05 + 1e-5, "half width {} stays unscaled", (y - 0.4).abs());
+    }
+}
+
+#[test]
+fn clear_keeps_only_the_identity_transform() {
+    let mut geom = Geometry::new();
+    let t = glam::Mat3::from_translation(Vec2::ONE);
+    assert_eq!(geom.push_transform(None), 0);
+    assert_eq!(geom.push_transform(Some(&t)), 1);
+    assert_eq!(geom.push_transform(Some(&glam::Mat3::IDENTITY)), 2, "only consecutive transforms are shared");
+
+    geom.clear();
+    assert_eq!(geom.transforms, vec![glam::Mat3::IDENTITY]);
+}
+
+#[test]
+fn extend_from_offsets_indices_and_transforms() {
+    let t = glam::Mat3::from_translation(Vec2::ONE);
+    let vertex = |transform| Vertex { transform, ..Vertex::default() };
+    let mut a = Geometry::new();
+    a.transforms.push(t);
+    a.vertices = vec![vertex(0), vertex(1), vertex(1)];
+    a.indices = vec![0, 1, 2];
+    let mut merged = a.clone();
+
+    merged.extend_from(&a);
+    assert_eq!(merged.transforms, vec![glam::Mat3::IDENTITY, t, t]);
+    assert_eq!(merged.indices, vec![0, 1, 2, 3, 4, 5]);
+    let transforms: Vec<u32> = merged.vertices.iter().map(|v| v.transform).collect();
+    assert_eq!(transforms, vec![0, 1, 1, 0, 2, 2], "identity stays shared");
+}
@@ -0,0 +1,89 @@
+//! Tests for the polyline tessellation cache, observed through SoftwareRenderer.
+
+use glam::{Mat3, Vec2};
+use vectorcade_render_wgpu::testing::diff_images;
+use vectorcade_render_wgpu::{SoftwareRenderer, VectorRenderer};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Stroke};
+
+fn ship() -> DrawCmd {
+    DrawCmd::Polyline {
+        pts: vec![Vec2::new(0.0, 0.1), Vec2::new(-0.06, -0.08), Vec2::new(0.06, -0.08)],
+        closed: true,
+        stroke: Stroke::new(Rgba::WHITE, 2.0),
+    }
+}
+
+fn transformed(t: Mat3, cmd: DrawCmd) -> [DrawCmd; 3] {
+    [DrawCmd::PushTransform(t), cmd, DrawCmd::PopTransform]
+}
+
+fn fleet() -> Vec<DrawCmd> {
+    let mut cmds = vec![DrawCmd::Clear { color: Rgba::BLACK }];
+    for i in 0..4 {
+        let angle = i as f32 * 0.7;
+        let t = Mat3::from_scale_angle_translation(Vec2::splat(2.0), angle, Vec2::new(i as f32 * 0.4 - 0.6, 0.0));
+        cmds.extend(transformed(t, ship()));
+    }
+    cmds
+}
+
+#[test]
+fn rotated_copies_reuse_one_mesh() {
+    let mut r = SoftwareRenderer::new(128, 128);
+    let stats = r.render(&fleet());
+    assert_eq!((stats.polylines, stats.cache_hits, stats.cache_misses), (4, 3, 1));
+
+    let stats = r.render(&fleet());
+    assert_eq!((stats.cache_hits, stats.cache_misses), (4, 0), "entries survive into the next frame");
+}
+
+#[test]
+fn cached_meshes_match_direct_tessellation() {
+    let mut cached = SoftwareRenderer::new(128, 128);
+    cached.render(&fleet());
+    cached.render(&fleet());
+
+    let mut direct = SoftwareRenderer::new(128, 128);
+    direct.set_cache_max_age(0);
+    let stats = direct.render(&fleet());
+    assert_eq!((stats.cache_hits, stats.cache_misses), (0, 4));
+
+    let diff = diff_images(cached.image(), direct.image(), 0.1).expect("same size");
+    assert!(diff.ratio() < 0.005, "{} pixels differ", diff.differing);
+}
+
+#[test]
+fn stroke_is_part_of_the_key_but_scale_is_not() {
+    let mut r = SoftwareRenderer::new(64, 64);
+    let wide = DrawCmd::Polyline {
+        pts: vec![Vec2::new(0.0, 0.1), Vec2::new(-0.06, -0.08), Vec2::new(0.06, -0.08)],
+        closed: true,
+        stroke: Stroke::new(Rgba::WHITE, 5.0),
+    };
+    let mut cmds = vec![ship(), wide];
+    cmds.extend(transformed(Mat3::from_scale(Vec2::splat(3.0)), ship()));
+    let stats = r.render(&cmds);
+    assert_eq!((stats.cache_hits, stats.cache_misses), (1, 2));
+}
+
+#[test]
+fn non_uniform_scale_reuses_meshes() {
+    let mut r = SoftwareRenderer::new(64, 64);
+    r.render(&[ship()]);
+    let stats = r.render(&transformed(Mat3::from_scale(Vec2::new(2.0, 1.0)), ship()));
+    assert_eq!((stats.cache_hits, stats.cache_misses), (1, 0));
+}
+
+#[test]
+fn unused_entries_are_evicted_after_max_age() {
+    let mut r = SoftwareRenderer::new(64, 64);
+    r.set_cache_max_age(2);
+    r.render(&[ship()]);
+    r.render(&[]);
+    assert_eq!(r.render(&[ship()]).cache_hits, 1, "one unused frame keeps the entry");
+
+    r.render(&[]);
+    r.render(&[]);
+    assert_eq!(r.render(&[ship()]).cache_misses, 1, "two unused frames evict it");
+}
@@ -0,0 +1,97 @@
+//! Mapping the game's world onto targets of another aspect ratio.
+
+#![cfg(feature = "wgpu-backend")]
+
+use glam::Vec2;
+use vectorcade_render_wgpu::{
+    CoordinateSystem, RgbaImage, SoftwareRenderer, VectorRenderer, ViewportMode, ViewportRect, WgpuRenderer, YAxis,
+};
+use vectorcade_shared::Rgba;
+use vectorcade_shared::draw::{DrawCmd, Line2, Stroke};
+
+#[test]
+fn viewport_rects_fit_fill_or_stretch() {
+    let bars = Rgba::BLACK;
+    let fit = ViewportMode::Fit { aspect: 1.0, bars };
+    assert_eq!(fit.rect(64, 32), ViewportRect { x: 16, y: 0, width: 32, height: 32 });
+    assert_eq!(fit.rect(32, 64), ViewportRect { x: 0, y: 16, width: 32, height: 32 });
+    let fill = ViewportMode::Fill { aspect: 1.0 };
+    assert_eq!(fill.rect(64, 32), ViewportRect { x: 0, y: -16, width: 64, height: 64 });
+    assert_eq!(ViewportMode::Stretch.rect(64, 32), ViewportRect { x: 0, y: 0, width: 64, height: 32 });
+    let invalid = ViewportMode::Fit { aspect: 0.0, bars };
+    assert_eq!(invalid.rect(64, 32), ViewportMode::Stretch.rect(64, 32));
+}
+
+/// `Fit` pillarboxes a square game on a wide target with the bar color.
+#[test]
+fn fit_draws_bars_beside_the_game() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(64, 32).await.expect("No adapter");
+        renderer.set_glow_intensity(0.0);
+        renderer.set_viewport_mode(ViewportMode::Fit { aspect: 1.0, bars: Rgba(1.0, 0.0, 0.0, 1.0) });
+        assert_eq!(renderer.viewport_rect(), ViewportRect { x: 16, y: 0, width: 32, height: 32 });
+
+        renderer.render(&[DrawCmd::Clear { color: Rgba(0.0, 0.0, 1.0, 1.0) }]);
+        let image = renderer.capture_frame().expect("capture failed");
+        assert_eq!(image.pixel(2, 16), [255, 0, 0, 255], "left bar");
+        assert_eq!(image.pixel(61, 16), [255, 0, 0, 255], "right bar");
+        assert_eq!(image.pixel(32, 16), [0, 0, 255, 255], "game area");
+
+        renderer.set_viewport_mode(ViewportMode::Stretch);
+        assert_eq!(renderer.viewport_rect(), ViewportRect { x: 0, y: 0, width: 64, height: 32 });
+        renderer.render(&[DrawCmd::Clear { color: Rgba(0.0, 0.0, 1.0, 1.0) }]);
+        assert_eq!(renderer.capture_frame().expect("capture failed").pixel(2, 16), [0, 0, 255, 255]);
+    });
+}
+
+/// Pixel coordinates address the viewport rect, y down, with strokes still in target pixels.
+#[test]
+fn pixel_coordinates_address_the_viewport_rect() {
+    pollster::block_on(async {
+        let mut renderer = WgpuRenderer::new_headless(64, 32).await.expect("No adapter");
+        renderer.set_glow_intensity(0.0);
+        renderer.set_viewport_mode(ViewportMode::Fit { aspect: 1.0, bars: Rgba::BLACK });
+        renderer.set_coordinate_system(CoordinateSystem::Pixels(YAxis::Down));
+        let stroke = Stroke::new(Rgba::WHITE, 4.0);
+        renderer.render(&[
+            DrawCmd::Clear { color: Rgba::BLACK },
+            DrawCmd::Line(Line2 { a: Vec2::new(16.0, 8.0), b: Vec2::new(32.0, 8.0), stroke }),
+        ]);
+
+        let image = renderer.capture_frame().expect("capture failed");
+        assert_eq!(image.pixel(40, 7), [255, 255, 255, 255], "8 px below the rect's top edge");
+        assert_eq!(image.pixel(40, 12), [0, 0, 0, 255], "4 px wide");
+        assert_eq!(image.pixel(24, 7), [0, 0, 0, 255], "x is relative to the rect");
+    });
+}
+
+/// Lit pixels in row `y` and in column `x` of `image`.
+fn lit_extents(image: &RgbaImage, x: u32, y: u32) -> (usize, usize) {
+    let lit = |px: [u8; 4]| px[0] > 127;
+    let row = (0..image.width).filter(|&i| lit(image.pixel(i, y))).count();
+    let column = (0..image.height).filter(|&j| lit(image.pixel(x, j))).count();
+    (row, column)
+}
+
+/// On a 4:3 target both renderers draw a cross's arms as wide as they are tall.
+#[test]
+fn stroke_widths_match_across_axes_and_renderers() {
+    let stroke = Stroke::new(Rgba::WHITE, 10.0);
+    let cmds = [
+        DrawCmd::Clear { color: Rgba::BLACK },
+        DrawCmd::Line(Line2 { a: Vec2::new(-0.5, 0.0), b: Vec2::new(0.5, 0.0), stroke }),
+        DrawCmd::Line(Line2 { a: Vec2::new(0.0, -0.5), b: Vec2::new(0.0, 0.5), stroke }),
+    ];
+    // Row 60 crosses only the vertical arm and column 80 only the horizontal one.
+    let mut software = SoftwareRenderer::new(320, 240);
+    software.render(&cmds);
+    assert_eq!(lit_extents(software.image(), 80, 60), (10, 10), "software arm widths");
+
+    pollster::block_on(async {
+        let mut gpu = WgpuRenderer::new_headless(320, 240).await.expect("No adapter");
+        gpu.set_glow_intensity(0.0);
+        gpu.render(&cmds);
+        let image = gpu.capture_frame().expect("capture failed");
+        assert_eq!(lit_extents(&image, 80, 60), (10, 10), "GPU arm widths");
+    });
+}